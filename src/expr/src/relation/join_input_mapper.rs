@@ -39,21 +39,21 @@ impl JoinInputMapper {
     /// Creates a new `JoinInputMapper` and calculates the mapping of global context
     /// columns to local context columns.
     pub fn new(inputs: &[RelationExpr]) -> Self {
-        Self::new_from_input_types(&inputs.iter().map(|i| i.typ()).collect::<Vec<_>>())
+        Self::new_from_arities(inputs.iter().map(|i| i.arity()).collect())
     }
 
     /// Creates a new `JoinInputMapper` and calculates the mapping of global context
     /// columns to local context columns. Using this method saves is more
     /// efficient if input types have been pre-calculated
     pub fn new_from_input_types(types: &[RelationType]) -> Self {
-        let arities = types
-            .iter()
-            .map(|t| t.column_types.len())
-            .collect::<Vec<_>>();
+        Self::new_from_arities(types.iter().map(|t| t.column_types.len()).collect())
+    }
 
+    /// Creates a new `JoinInputMapper` from the number of columns of each input.
+    pub fn new_from_arities(arities: Vec<usize>) -> Self {
         let mut offset = 0;
         let mut prior_arities = Vec::new();
-        for input in 0..types.len() {
+        for input in 0..arities.len() {
             prior_arities.push(offset);
             offset += arities[input];
         }
@@ -464,4 +464,59 @@ mod tests {
             input_mapper.try_map_to_input_with_bound_expr(key_comp_multi_input, 1, &equivalences)
         )
     }
+
+    #[test]
+    fn split_column_set_by_input_test() {
+        // Joins of two to five inputs of distinct arities, including an input
+        // without columns, which owns no global column.
+        let arities = vec![
+            vec![1, 2],
+            vec![3, 1, 2],
+            vec![2, 0, 4, 1],
+            vec![4, 3, 1, 5, 2],
+        ];
+        for arities in arities {
+            let types = arities
+                .iter()
+                .map(|arity| RelationType::new(vec![ScalarType::Int64.nullable(true); *arity]))
+                .collect::<Vec<_>>();
+            let input_mapper = JoinInputMapper::new_from_input_types(&types);
+            let total: usize = arities.iter().sum();
+            assert_eq!(input_mapper.total_columns(), total);
+
+            // The first and last columns of each input, and every third column
+            // of the join, so that the set spans each boundary between inputs.
+            let mut columns = (0..total).step_by(3).collect::<HashSet<_>>();
+            let mut expected = vec![HashSet::new(); arities.len()];
+            let mut offset = 0;
+            for arity in arities.iter() {
+                if *arity > 0 {
+                    columns.insert(offset);
+                    columns.insert(offset + arity - 1);
+                }
+                offset += arity;
+            }
+            let mut offset = 0;
+            for (input, arity) in arities.iter().enumerate() {
+                for local in 0..*arity {
+                    if columns.contains(&(offset + local)) {
+                        expected[input].insert(local);
+                    }
+                }
+                offset += arity;
+            }
+
+            assert_eq!(
+                input_mapper.split_column_set_by_input(&columns),
+                expected,
+                "arities: {:?}",
+                arities
+            );
+            for column in columns {
+                let (local, input) = input_mapper.map_column_to_local(column);
+                assert!(local < arities[input], "column: {}", column);
+                assert_eq!(input_mapper.map_column_to_global(local, input), column);
+            }
+        }
+    }
 }
@@ -1012,6 +1012,8 @@ impl RelationExpr {
     }
 
     /// Take ownership of `self`, leaving an empty `RelationExpr::Constant` with the correct type.
+    ///
+    /// The type retains the unique keys of `self`, all of which an empty collection satisfies.
     pub fn take_safely(&mut self) -> RelationExpr {
         let typ = self.typ();
         std::mem::replace(self, RelationExpr::Constant { rows: vec![], typ })
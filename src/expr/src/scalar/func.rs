@@ -3888,6 +3888,13 @@ impl VariadicFunc {
     }
 
     /// Whether the function output is NULL if any of its inputs are NULL.
+    ///
+    /// Functions are assumed to propagate nulls unless listed here. Those that
+    /// skip null inputs, like `coalesce` or SQL's `greatest` and `least`, must
+    /// be listed, or `ScalarExpr::non_null_requirements` would require each of
+    /// their inputs. So must those that propagate nulls from only some of
+    /// their inputs, like `array_to_string`, which `non_null_requirements`
+    /// handles separately.
     pub fn propagates_nulls(&self) -> bool {
         !matches!(self, VariadicFunc::Coalesce
             | VariadicFunc::Concat
@@ -497,12 +497,73 @@ impl ScalarExpr {
                     expr.non_null_requirements(columns);
                 }
             }
+            // `a AND b` may be false, and `a OR b` true, when either operand
+            // is null, and so only requirements common to both operands, which
+            // make both null, remain.
+            ScalarExpr::CallBinary {
+                func: BinaryFunc::And,
+                expr1,
+                expr2,
+            }
+            | ScalarExpr::CallBinary {
+                func: BinaryFunc::Or,
+                expr1,
+                expr2,
+            } => {
+                let mut columns1 = HashSet::new();
+                expr1.non_null_requirements(&mut columns1);
+                let mut columns2 = HashSet::new();
+                expr2.non_null_requirements(&mut columns2);
+                columns.extend(columns1.intersection(&columns2));
+            }
+            // Non-null operands are necessary but not sufficient for a non-null
+            // result. For example, `a / b` requires both `a` and `b`, but is an
+            // error rather than a value when `b` is zero, and `data ->> 'name'`
+            // requires `data`, but is null when `data` lacks the key.
             ScalarExpr::CallBinary { func, expr1, expr2 } => {
                 if func.propagates_nulls() {
                     expr1.non_null_requirements(columns);
                     expr2.non_null_requirements(columns);
                 }
             }
+            ScalarExpr::CallVariadic {
+                func: VariadicFunc::Coalesce,
+                exprs,
+            } => {
+                // `coalesce` is non-null when *any* argument is, which may be
+                // a different argument for each row. Requiring the last
+                // argument, or any other, would discard rows that an earlier
+                // argument makes non-null. Only the requirements common to
+                // all arguments, without which each would be null, remain.
+                let mut requirements = exprs.iter().map(|expr| {
+                    let mut columns = HashSet::new();
+                    expr.non_null_requirements(&mut columns);
+                    columns
+                });
+                if let Some(mut common) = requirements.next() {
+                    for expr_columns in requirements {
+                        common.retain(|c| expr_columns.contains(c));
+                    }
+                    columns.extend(common);
+                }
+            }
+            // `array_to_string(a, d, n)` is null if `a` or `d` is, but takes a
+            // null `n` to mean that null elements are skipped, and so requires
+            // only its first two arguments, where `concat`, which treats
+            // nulls as empty strings, requires none.
+            ScalarExpr::CallVariadic {
+                func: VariadicFunc::ArrayToString { .. },
+                exprs,
+            } => {
+                for expr in exprs.iter().take(2) {
+                    expr.non_null_requirements(columns);
+                }
+            }
+            // Constructors of records, lists and arrays produce non-null
+            // composites even from null elements, and so require nothing of
+            // them, unlike functions that extract a field. Other functions
+            // that propagate nulls require each argument, which is necessary
+            // but not sufficient: `regexp_match` is null when nothing matches.
             ScalarExpr::CallVariadic { func, exprs } => {
                 if func.propagates_nulls() {
                     for expr in exprs {
@@ -510,7 +571,63 @@ impl ScalarExpr {
                     }
                 }
             }
-            ScalarExpr::If { .. } => (),
+            ScalarExpr::If { cond: _, then, els } => {
+                // Either branch may produce the result, and so only the
+                // requirements common to both are requirements of `self`.
+                // The condition is not among them, as a null condition
+                // selects `els`.
+                //
+                // A branch that is literally null cannot produce a non-null
+                // result, and so the requirements are those of the other
+                // branch. `nullif(a, b)`, which plans as
+                // `if a = b then null else a`, requires `a` but not `b`.
+                if then.is_literal_null() {
+                    els.non_null_requirements(columns);
+                } else if els.is_literal_null() {
+                    then.non_null_requirements(columns);
+                } else {
+                    let mut then_columns = HashSet::new();
+                    then.non_null_requirements(&mut then_columns);
+                    let mut els_columns = HashSet::new();
+                    els.non_null_requirements(&mut els_columns);
+                    columns.extend(then_columns.intersection(&els_columns));
+                }
+            }
+        }
+    }
+
+    /// True if `self` is certainly null whenever `column` is null.
+    ///
+    /// Functions that may produce a non-null result from a null input, like
+    /// `isnull` or `coalesce`, are only null-rejecting when each of the inputs
+    /// that could produce their result is.
+    pub fn null_rejecting(&self, column: usize) -> bool {
+        match self {
+            ScalarExpr::Column(col) => *col == column,
+            ScalarExpr::Literal(..) => false,
+            ScalarExpr::CallNullary(_) => false,
+            ScalarExpr::CallUnary { func, expr } => {
+                func.propagates_nulls() && expr.null_rejecting(column)
+            }
+            ScalarExpr::CallBinary { func, expr1, expr2 } => {
+                func.propagates_nulls()
+                    && (expr1.null_rejecting(column) || expr2.null_rejecting(column))
+            }
+            ScalarExpr::CallVariadic {
+                func: VariadicFunc::Coalesce,
+                exprs,
+            } => exprs.iter().all(|expr| expr.null_rejecting(column)),
+            ScalarExpr::CallVariadic {
+                func: VariadicFunc::ArrayToString { .. },
+                exprs,
+            } => exprs.iter().take(2).any(|expr| expr.null_rejecting(column)),
+            ScalarExpr::CallVariadic { func, exprs } => {
+                func.propagates_nulls() && exprs.iter().any(|expr| expr.null_rejecting(column))
+            }
+            ScalarExpr::If { cond: _, then, els } => {
+                (then.is_literal_null() || then.null_rejecting(column))
+                    && (els.is_literal_null() || els.null_rejecting(column))
+            }
         }
     }
 
@@ -664,6 +781,8 @@ impl From<regex::Error> for EvalError {
 
 #[cfg(test)]
 mod tests {
+    use repr::DatumMap;
+
     use super::*;
 
     #[test]
@@ -771,4 +890,750 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_if_non_null_requirements() {
+        let col = |i| ScalarExpr::Column(i);
+        let requirements = |expr: ScalarExpr| {
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            let mut columns = columns.into_iter().collect::<Vec<_>>();
+            columns.sort();
+            columns
+        };
+
+        // CASE WHEN #0 THEN #1 ELSE #2 END
+        assert_eq!(
+            requirements(col(0).if_then_else(col(1), col(2))),
+            Vec::<usize>::new()
+        );
+        // CASE WHEN #0 THEN #1 + #2 ELSE #2 END
+        assert_eq!(
+            requirements(
+                col(0).if_then_else(col(1).call_binary(col(2), BinaryFunc::AddInt64), col(2))
+            ),
+            vec![2]
+        );
+        // CASE WHEN #0 THEN #1 + #2 ELSE #2 + #1 END
+        assert_eq!(
+            requirements(col(0).if_then_else(
+                col(1).call_binary(col(2), BinaryFunc::AddInt64),
+                col(2).call_binary(col(1), BinaryFunc::AddInt64)
+            )),
+            vec![1, 2]
+        );
+        // CASE WHEN #0 THEN #0 ELSE #0 END
+        assert_eq!(requirements(col(0).if_then_else(col(0), col(0))), vec![0]);
+    }
+
+    #[test]
+    fn test_null_rejecting() {
+        let col = |i| ScalarExpr::Column(i);
+        let lit = |i| ScalarExpr::literal_ok(Datum::Int64(i), ScalarType::Int64.nullable(false));
+        let coalesce = |exprs| ScalarExpr::CallVariadic {
+            func: VariadicFunc::Coalesce,
+            exprs,
+        };
+
+        struct TestCase {
+            expr: ScalarExpr,
+            column: usize,
+            null_rejecting: bool,
+        }
+
+        let test_cases = vec![
+            TestCase {
+                expr: col(0),
+                column: 0,
+                null_rejecting: true,
+            },
+            TestCase {
+                expr: col(1),
+                column: 0,
+                null_rejecting: false,
+            },
+            TestCase {
+                expr: lit(1),
+                column: 0,
+                null_rejecting: false,
+            },
+            // #0 + 0
+            TestCase {
+                expr: col(0).call_binary(lit(0), BinaryFunc::AddInt64),
+                column: 0,
+                null_rejecting: true,
+            },
+            // (#0 * #1) + 1
+            TestCase {
+                expr: col(0)
+                    .call_binary(col(1), BinaryFunc::MulInt64)
+                    .call_binary(lit(1), BinaryFunc::AddInt64),
+                column: 1,
+                null_rejecting: true,
+            },
+            // isnull(#0)
+            TestCase {
+                expr: col(0).call_unary(UnaryFunc::IsNull),
+                column: 0,
+                null_rejecting: false,
+            },
+            // !isnull(#0) is not null-rejecting through its argument.
+            TestCase {
+                expr: col(0)
+                    .call_unary(UnaryFunc::IsNull)
+                    .call_unary(UnaryFunc::Not),
+                column: 0,
+                null_rejecting: false,
+            },
+            // #0 OR #1
+            TestCase {
+                expr: col(0).call_binary(col(1), BinaryFunc::Or),
+                column: 0,
+                null_rejecting: false,
+            },
+            TestCase {
+                expr: coalesce(vec![col(0), col(0)]),
+                column: 0,
+                null_rejecting: true,
+            },
+            TestCase {
+                expr: coalesce(vec![col(0), col(1)]),
+                column: 0,
+                null_rejecting: false,
+            },
+            TestCase {
+                expr: coalesce(vec![col(0), lit(1)]),
+                column: 0,
+                null_rejecting: false,
+            },
+            // CASE WHEN #1 THEN #0 ELSE #0 + 1 END
+            TestCase {
+                expr: col(1).if_then_else(col(0), col(0).call_binary(lit(1), BinaryFunc::AddInt64)),
+                column: 0,
+                null_rejecting: true,
+            },
+            // CASE WHEN #1 THEN #0 ELSE 1 END
+            TestCase {
+                expr: col(1).if_then_else(col(0), lit(1)),
+                column: 0,
+                null_rejecting: false,
+            },
+            // CASE WHEN #0 THEN 1 ELSE 2 END
+            TestCase {
+                expr: col(0).if_then_else(lit(1), lit(2)),
+                column: 0,
+                null_rejecting: false,
+            },
+        ];
+
+        for tc in test_cases {
+            assert_eq!(
+                tc.expr.null_rejecting(tc.column),
+                tc.null_rejecting,
+                "expr: {}, column: {}",
+                tc.expr,
+                tc.column
+            );
+        }
+    }
+
+    #[test]
+    fn test_record_get_non_null_requirements() {
+        // A field of a null record is null, so requires the record.
+        let mut columns = HashSet::new();
+        ScalarExpr::Column(1)
+            .call_unary(UnaryFunc::RecordGet(0))
+            .call_unary(UnaryFunc::RecordGet(2))
+            .non_null_requirements(&mut columns);
+        assert_eq!(columns, vec![1].into_iter().collect::<HashSet<_>>());
+        assert!(ScalarExpr::Column(1)
+            .call_unary(UnaryFunc::RecordGet(0))
+            .null_rejecting(1));
+    }
+
+    #[test]
+    fn test_coalesce_non_null_requirements() {
+        let coalesce = |exprs| ScalarExpr::CallVariadic {
+            func: VariadicFunc::Coalesce,
+            exprs,
+        };
+        let requirements = |expr: ScalarExpr| {
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            let mut columns = columns.into_iter().collect::<Vec<_>>();
+            columns.sort();
+            columns
+        };
+        let add = |c1: usize, c2: usize| {
+            ScalarExpr::column(c1).call_binary(ScalarExpr::column(c2), BinaryFunc::AddInt64)
+        };
+
+        // `coalesce(#0, #1)` is non-null if either `#0` or `#1` is, and so
+        // requires neither of them in particular.
+        let expr = coalesce(vec![ScalarExpr::column(0), ScalarExpr::column(1)]);
+        assert_eq!(requirements(expr), Vec::<usize>::new());
+
+        // Each argument of `coalesce(#0 + #1, #1 + #2)` is null if `#1` is.
+        let expr = coalesce(vec![add(0, 1), add(1, 2)]);
+        assert_eq!(requirements(expr), vec![1]);
+
+        let expr = coalesce(vec![add(0, 1)]);
+        assert_eq!(requirements(expr), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_and_or_non_null_requirements() {
+        let col = ScalarExpr::Column;
+        let requirements = |expr: ScalarExpr| {
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            let mut columns = columns.into_iter().collect::<Vec<_>>();
+            columns.sort();
+            columns
+        };
+        for func in vec![BinaryFunc::And, BinaryFunc::Or] {
+            // `false AND null` is false, and `true OR null` is true.
+            let expr = col(0).call_binary(col(1), func.clone());
+            assert_eq!(requirements(expr), Vec::<usize>::new());
+
+            // Both operands are null if `#1` is.
+            let expr = col(0)
+                .call_binary(col(1), BinaryFunc::Eq)
+                .call_binary(col(1).call_binary(col(2), BinaryFunc::Eq), func);
+            assert_eq!(requirements(expr), vec![1]);
+        }
+    }
+
+    #[test]
+    fn test_datetime_non_null_requirements() {
+        let col = ScalarExpr::Column;
+        let units = |units: &str| {
+            ScalarExpr::literal_ok(Datum::String(units), ScalarType::String.nullable(false))
+        };
+        // `extract(year FROM #0)` plans as `date_part('year', #0)`, which
+        // reduction may specialize to the units.
+        let exprs = vec![
+            units("year").call_binary(col(0), BinaryFunc::DatePartTimestamp),
+            units("year").call_binary(col(0), BinaryFunc::DatePartTimestampTz),
+            col(0).call_unary(UnaryFunc::DatePartTimestamp(DateTimeUnits::Year)),
+            col(0).call_unary(UnaryFunc::DatePartTimestampTz(DateTimeUnits::Year)),
+            units("day").call_binary(col(0), BinaryFunc::DateTruncTimestamp),
+            units("day").call_binary(col(0), BinaryFunc::DateTruncTimestampTz),
+            col(0).call_unary(UnaryFunc::DateTruncTimestamp(DateTimeUnits::Day)),
+            col(0).call_unary(UnaryFunc::DateTruncTimestampTz(DateTimeUnits::Day)),
+        ];
+        for expr in exprs {
+            // As in `WHERE extract(year FROM #0) = 2020`.
+            let predicate = expr.clone().call_binary(
+                ScalarExpr::literal_ok(
+                    Datum::Float64(2020.0f64.into()),
+                    ScalarType::Float64.nullable(false),
+                ),
+                BinaryFunc::Eq,
+            );
+            for expr in vec![expr, predicate] {
+                let mut columns = HashSet::new();
+                expr.non_null_requirements(&mut columns);
+                assert_eq!(
+                    columns,
+                    vec![0].into_iter().collect::<HashSet<_>>(),
+                    "expr: {}",
+                    expr
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_division_non_null_requirements() {
+        let col = ScalarExpr::Column;
+        let one = ScalarExpr::literal_ok(Datum::Int64(1), ScalarType::Int64.nullable(false));
+        for func in vec![
+            BinaryFunc::DivInt32,
+            BinaryFunc::DivInt64,
+            BinaryFunc::DivFloat32,
+            BinaryFunc::DivFloat64,
+            BinaryFunc::DivDecimal,
+            BinaryFunc::ModInt32,
+            BinaryFunc::ModInt64,
+            BinaryFunc::ModFloat32,
+            BinaryFunc::ModFloat64,
+            BinaryFunc::ModDecimal,
+        ] {
+            // As in `WHERE #0 / #1 > 1`.
+            let expr = col(0)
+                .call_binary(col(1), func)
+                .call_binary(one.clone(), BinaryFunc::Gt);
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            assert_eq!(
+                columns,
+                vec![0, 1].into_iter().collect::<HashSet<_>>(),
+                "expr: {}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn test_constructor_non_null_requirements() {
+        let variadic = |func| ScalarExpr::CallVariadic {
+            func,
+            exprs: vec![ScalarExpr::Column(0), ScalarExpr::Column(1)],
+        };
+        let funcs = vec![
+            VariadicFunc::RecordCreate {
+                field_names: vec!["a".into(), "b".into()],
+            },
+            VariadicFunc::ListCreate {
+                elem_type: ScalarType::Int64,
+            },
+            VariadicFunc::ArrayCreate {
+                elem_type: ScalarType::Int64,
+            },
+            VariadicFunc::JsonbBuildArray,
+        ];
+        for func in funcs {
+            let expr = variadic(func);
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            assert!(columns.is_empty(), "expr: {}", expr);
+            assert!(!expr.null_rejecting(0), "expr: {}", expr);
+        }
+    }
+
+    #[test]
+    fn test_nullif_non_null_requirements() {
+        let col = ScalarExpr::Column;
+        let null = || ScalarExpr::literal_null(ScalarType::Int64.nullable(true));
+        let zero = ScalarExpr::literal_ok(Datum::Int64(0), ScalarType::Int64.nullable(false));
+        let requirements = |expr: &ScalarExpr| {
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            let mut columns = columns.into_iter().collect::<Vec<_>>();
+            columns.sort();
+            columns
+        };
+
+        // `nullif(#0, #1) > 0`, where `nullif(#0, #1)` plans as
+        // `CASE WHEN #0 = #1 THEN NULL ELSE #0 END`.
+        let nullif = col(0)
+            .call_binary(col(1), BinaryFunc::Eq)
+            .if_then_else(null(), col(0));
+        let expr = nullif.call_binary(zero.clone(), BinaryFunc::Gt);
+        assert_eq!(requirements(&expr), vec![0]);
+        assert!(expr.null_rejecting(0));
+        assert!(!expr.null_rejecting(1));
+
+        // Likewise with the null in the other branch.
+        let expr = col(0)
+            .call_binary(col(1), BinaryFunc::NotEq)
+            .if_then_else(col(0), null());
+        assert_eq!(requirements(&expr), vec![0]);
+
+        // `#0 IS NULL` is never null, and requires nothing.
+        let expr = col(0).call_unary(UnaryFunc::IsNull);
+        assert_eq!(requirements(&expr), Vec::<usize>::new());
+        assert!(!expr.null_rejecting(0));
+    }
+
+    #[test]
+    fn test_pattern_match_non_null_requirements() {
+        let relation_type = RelationType::new(vec![ScalarType::String.nullable(true)]);
+        let pattern = |pattern| {
+            ScalarExpr::literal_ok(Datum::String(pattern), ScalarType::String.nullable(false))
+        };
+        // `#0 LIKE 'a%'`, `#0 ~ 'a'` and `#0 ~* 'a'`.
+        let exprs = vec![
+            ScalarExpr::Column(0).call_binary(pattern("a%"), BinaryFunc::IsLikePatternMatch),
+            ScalarExpr::Column(0).call_binary(
+                pattern("a"),
+                BinaryFunc::IsRegexpMatch {
+                    case_insensitive: false,
+                },
+            ),
+            ScalarExpr::Column(0).call_binary(
+                pattern("a"),
+                BinaryFunc::IsRegexpMatch {
+                    case_insensitive: true,
+                },
+            ),
+        ];
+        for expr in exprs {
+            // Reduction precompiles the literal pattern into a unary match.
+            let mut reduced = expr.clone();
+            reduced.reduce(&relation_type);
+            assert!(
+                matches!(reduced, ScalarExpr::CallUnary { func: UnaryFunc::IsRegexpMatch(_), .. }),
+                "reduced: {}",
+                reduced
+            );
+            for expr in vec![expr, reduced] {
+                let mut columns = HashSet::new();
+                expr.non_null_requirements(&mut columns);
+                assert_eq!(
+                    columns,
+                    vec![0].into_iter().collect::<HashSet<_>>(),
+                    "expr: {}",
+                    expr
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_jsonb_get_non_null_requirements() {
+        let relation_type = RelationType::new(vec![ScalarType::Jsonb.nullable(true)]);
+        let string =
+            |s| ScalarExpr::literal_ok(Datum::String(s), ScalarType::String.nullable(false));
+        for stringify in vec![false, true] {
+            // As in `WHERE (data ->> 'name') = 'x'`, or with `->` in place of
+            // `->>`, where `data` is `#0`.
+            let expr = ScalarExpr::Column(0)
+                .call_binary(string("name"), BinaryFunc::JsonbGetString { stringify })
+                .call_binary(string("x"), BinaryFunc::Eq);
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            assert_eq!(
+                columns,
+                vec![0].into_iter().collect::<HashSet<_>>(),
+                "expr: {}",
+                expr
+            );
+            assert!(expr.null_rejecting(0), "expr: {}", expr);
+        }
+
+        // A missing key gives null from a non-null object, so the object is
+        // necessary but not sufficient for a non-null result.
+        let expr = ScalarExpr::literal_ok(
+            Datum::Map(DatumMap::empty()),
+            ScalarType::Jsonb.nullable(false),
+        )
+        .call_binary(
+            string("name"),
+            BinaryFunc::JsonbGetString { stringify: true },
+        );
+        let mut reduced = expr.clone();
+        reduced.reduce(&relation_type);
+        assert!(reduced.is_literal_null(), "reduced: {}", reduced);
+    }
+
+    #[test]
+    fn test_formatting_non_null_requirements() {
+        let string =
+            |s| ScalarExpr::literal_ok(Datum::String(s), ScalarType::String.nullable(false));
+        let mut exprs = vec![];
+        // As in `WHERE to_char(#0, 'YYYY') = '2020'`, over a timestamp or a
+        // timestamp with time zone.
+        for func in vec![BinaryFunc::ToCharTimestamp, BinaryFunc::ToCharTimestampTz] {
+            exprs.push(
+                ScalarExpr::Column(0)
+                    .call_binary(string("YYYY"), func)
+                    .call_binary(string("2020"), BinaryFunc::Eq),
+            );
+        }
+        // `to_timestamp(#0)` is non-null only if `#0` is, and finite, as an
+        // infinite argument gives null.
+        exprs.push(ScalarExpr::Column(0).call_unary(UnaryFunc::ToTimestamp));
+        for expr in exprs {
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            assert_eq!(
+                columns,
+                vec![0].into_iter().collect::<HashSet<_>>(),
+                "expr: {}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn test_encoding_non_null_requirements() {
+        let string =
+            |s| ScalarExpr::literal_ok(Datum::String(s), ScalarType::String.nullable(false));
+        let zero = ScalarExpr::literal_ok(Datum::Int32(0), ScalarType::Int32.nullable(false));
+        // As in `WHERE length(convert_from(#0, 'utf8')) > 0` and `WHERE
+        // length(#0, 'utf8') > 0`. The encoding is a literal, and requires no
+        // columns. A non-null `#0` is necessary but not sufficient, as bytes
+        // that are not valid in the encoding are an error rather than null.
+        let exprs = vec![
+            ScalarExpr::Column(0)
+                .call_binary(string("utf8"), BinaryFunc::ConvertFrom)
+                .call_unary(UnaryFunc::CharLength),
+            ScalarExpr::Column(0).call_binary(string("utf8"), BinaryFunc::EncodedBytesCharLength),
+        ];
+        for expr in exprs {
+            let expr = expr.call_binary(zero.clone(), BinaryFunc::Gt);
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            assert_eq!(
+                columns,
+                vec![0].into_iter().collect::<HashSet<_>>(),
+                "expr: {}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn test_regexp_non_null_requirements() {
+        let relation_type = RelationType::new(vec![ScalarType::String.nullable(true)]);
+        let string =
+            |s| ScalarExpr::literal_ok(Datum::String(s), ScalarType::String.nullable(false));
+        // `regexp_match(#0, 'a')`, with and without flags, and `replace(#0,
+        // 'a', 'b')`.
+        let variadic = |func, mut exprs: Vec<ScalarExpr>| {
+            exprs.insert(0, ScalarExpr::Column(0));
+            ScalarExpr::CallVariadic { func, exprs }
+        };
+        let exprs = vec![
+            variadic(VariadicFunc::RegexpMatch, vec![string("a")]),
+            variadic(VariadicFunc::RegexpMatch, vec![string("a"), string("i")]),
+            variadic(VariadicFunc::Replace, vec![string("a"), string("b")]),
+        ];
+        for expr in exprs {
+            let mut reduced = expr.clone();
+            reduced.reduce(&relation_type);
+            for expr in vec![expr, reduced] {
+                let mut columns = HashSet::new();
+                expr.non_null_requirements(&mut columns);
+                assert_eq!(
+                    columns,
+                    vec![0].into_iter().collect::<HashSet<_>>(),
+                    "expr: {}",
+                    expr
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_concat_non_null_requirements() {
+        let requirements = |func, arity| {
+            let expr = ScalarExpr::CallVariadic {
+                func,
+                exprs: (0..arity).map(ScalarExpr::Column).collect(),
+            };
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            let mut columns = columns.into_iter().collect::<Vec<_>>();
+            columns.sort();
+            let rejecting = (0..arity)
+                .filter(|c| expr.null_rejecting(*c))
+                .collect::<Vec<_>>();
+            assert_eq!(columns, rejecting, "expr: {}", expr);
+            columns
+        };
+
+        // `concat(#0, #1)` treats nulls as empty strings, and requires nothing.
+        assert_eq!(requirements(VariadicFunc::Concat, 2), Vec::<usize>::new());
+
+        // `array_to_string(#0, #1, #2)` requires the array and delimiter, but
+        // not the replacement for null elements.
+        let array_to_string = || VariadicFunc::ArrayToString {
+            elem_type: ScalarType::Int64,
+        };
+        assert_eq!(requirements(array_to_string(), 3), vec![0, 1]);
+        assert_eq!(requirements(array_to_string(), 2), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_subscript_non_null_requirements() {
+        let int64 = |i| ScalarExpr::literal_ok(Datum::Int64(i), ScalarType::Int64.nullable(false));
+        let x = ScalarExpr::literal_ok(Datum::String("x"), ScalarType::String.nullable(false));
+        // As in `WHERE tags[1] = 'x'`, where `tags` is `#0`, over an array or
+        // a list. An index out of bounds gives null too, and so requiring
+        // `tags` is necessary but not sufficient.
+        let mut exprs = vec![];
+        for func in vec![BinaryFunc::ArrayIndex, BinaryFunc::ListIndex] {
+            exprs.push(
+                ScalarExpr::Column(0)
+                    .call_binary(int64(1), func)
+                    .call_binary(x.clone(), BinaryFunc::Eq),
+            );
+        }
+        // `tags[1:2]`, which the bounds may also make null.
+        exprs.push(ScalarExpr::CallVariadic {
+            func: VariadicFunc::ListSlice,
+            exprs: vec![ScalarExpr::Column(0), int64(1), int64(2)],
+        });
+        for expr in exprs {
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            assert_eq!(
+                columns,
+                vec![0].into_iter().collect::<HashSet<_>>(),
+                "expr: {}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn test_non_null_requirements_matrix() {
+        let col = ScalarExpr::Column;
+        let int64 = |i| ScalarExpr::literal_ok(Datum::Int64(i), ScalarType::Int64.nullable(false));
+        let string =
+            |s| ScalarExpr::literal_ok(Datum::String(s), ScalarType::String.nullable(false));
+        let requirements = |expr: &ScalarExpr| {
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            let mut columns = columns.into_iter().collect::<Vec<_>>();
+            columns.sort();
+            columns
+        };
+
+        // Each row gives the columns that must be non-null for the expression
+        // to be non-null. A function whose strictness changes should change
+        // its row, and a new function should add one.
+        let rows: Vec<(&str, ScalarExpr, Vec<usize>)> = vec![
+            ("literal", int64(1), vec![]),
+            ("column", col(0), vec![0]),
+            // Arithmetic.
+            (
+                "add",
+                col(0).call_binary(col(1), BinaryFunc::AddInt64),
+                vec![0, 1],
+            ),
+            ("neg", col(0).call_unary(UnaryFunc::NegInt64), vec![0]),
+            ("abs", col(0).call_unary(UnaryFunc::AbsInt64), vec![0]),
+            // Comparison.
+            ("eq", col(0).call_binary(col(1), BinaryFunc::Eq), vec![0, 1]),
+            ("lt", col(0).call_binary(int64(1), BinaryFunc::Lt), vec![0]),
+            // String.
+            (
+                "text concat",
+                col(0).call_binary(col(1), BinaryFunc::TextConcat),
+                vec![0, 1],
+            ),
+            (
+                "trim",
+                col(0).call_binary(string(" "), BinaryFunc::Trim),
+                vec![0],
+            ),
+            (
+                "char length",
+                col(0).call_unary(UnaryFunc::CharLength),
+                vec![0],
+            ),
+            // Date.
+            (
+                "date part",
+                string("year").call_binary(col(0), BinaryFunc::DatePartTimestamp),
+                vec![0],
+            ),
+            // JSON.
+            (
+                "jsonb get",
+                col(0).call_binary(string("a"), BinaryFunc::JsonbGetString { stringify: true }),
+                vec![0],
+            ),
+            // Logical.
+            ("and", col(0).call_binary(col(1), BinaryFunc::And), vec![]),
+            ("or", col(0).call_binary(col(1), BinaryFunc::Or), vec![]),
+            ("not", col(0).call_unary(UnaryFunc::Not), vec![0]),
+            ("is null", col(0).call_unary(UnaryFunc::IsNull), vec![]),
+            // Coalesce.
+            (
+                "coalesce",
+                ScalarExpr::CallVariadic {
+                    func: VariadicFunc::Coalesce,
+                    exprs: vec![col(0), col(1)],
+                },
+                vec![],
+            ),
+            (
+                "coalesce of common column",
+                ScalarExpr::CallVariadic {
+                    func: VariadicFunc::Coalesce,
+                    exprs: vec![col(0).call_binary(col(1), BinaryFunc::AddInt64), col(0)],
+                },
+                vec![0],
+            ),
+            // Case.
+            ("case", col(0).if_then_else(col(1), col(2)), vec![]),
+            (
+                "case of common column",
+                col(2).if_then_else(col(0), col(0).call_binary(col(1), BinaryFunc::AddInt64)),
+                vec![0],
+            ),
+            // Cast.
+            (
+                "cast",
+                col(0).call_unary(UnaryFunc::CastInt64ToString),
+                vec![0],
+            ),
+            (
+                "cast null to jsonb",
+                col(0).call_unary(UnaryFunc::CastJsonbOrNullToJsonb),
+                vec![],
+            ),
+            // Composite.
+            (
+                "record get",
+                col(0).call_unary(UnaryFunc::RecordGet(1)),
+                vec![0],
+            ),
+            (
+                "array index",
+                col(0).call_binary(int64(1), BinaryFunc::ArrayIndex),
+                vec![0],
+            ),
+        ];
+        for (name, expr, expected) in rows {
+            assert_eq!(requirements(&expr), expected, "{}: {}", name, expr);
+        }
+
+        // Every variadic function, applied to `#0`, `#1` and `#2`. The match
+        // is exhaustive, so that a new variadic function must be classified.
+        let variadics = vec![
+            VariadicFunc::Coalesce,
+            VariadicFunc::Concat,
+            VariadicFunc::MakeTimestamp,
+            VariadicFunc::PadLeading,
+            VariadicFunc::Substr,
+            VariadicFunc::Replace,
+            VariadicFunc::JsonbBuildArray,
+            VariadicFunc::JsonbBuildObject,
+            VariadicFunc::ArrayCreate {
+                elem_type: ScalarType::Int64,
+            },
+            VariadicFunc::ArrayToString {
+                elem_type: ScalarType::Int64,
+            },
+            VariadicFunc::ListCreate {
+                elem_type: ScalarType::Int64,
+            },
+            VariadicFunc::RecordCreate {
+                field_names: vec!["a".into(), "b".into(), "c".into()],
+            },
+            VariadicFunc::ListSlice,
+            VariadicFunc::SplitPart,
+            VariadicFunc::RegexpMatch,
+        ];
+        for func in variadics {
+            let expected = match func {
+                VariadicFunc::Coalesce
+                | VariadicFunc::Concat
+                | VariadicFunc::JsonbBuildArray
+                | VariadicFunc::JsonbBuildObject
+                | VariadicFunc::ArrayCreate { .. }
+                | VariadicFunc::ListCreate { .. }
+                | VariadicFunc::RecordCreate { .. } => vec![],
+                VariadicFunc::ArrayToString { .. } => vec![0, 1],
+                VariadicFunc::MakeTimestamp
+                | VariadicFunc::PadLeading
+                | VariadicFunc::Substr
+                | VariadicFunc::Replace
+                | VariadicFunc::ListSlice
+                | VariadicFunc::SplitPart
+                | VariadicFunc::RegexpMatch => vec![0, 1, 2],
+            };
+            let expr = ScalarExpr::CallVariadic {
+                func,
+                exprs: vec![col(0), col(1), col(2)],
+            };
+            assert_eq!(requirements(&expr), expected, "{}", expr);
+        }
+    }
 }
@@ -9,9 +9,109 @@
 
 use std::env;
 use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::SystemTime;
+
+/// The SHA reported by development builds when no SHA can be determined.
+const UNKNOWN_SHA: &str = "0000000000000000000000000000000000000000";
 
 pub fn main() -> Result<(), Box<dyn Error>> {
     println!("cargo:rerun-if-changed=build.rs");
-    println!("cargo:rustc-env=TARGET_TRIPLE={}", env::var("TARGET")?);
+    // Packagers may stamp a canonical platform in place of the actual target,
+    // e.g. to report a musl build as a generic Linux build.
+    println!("cargo:rerun-if-env-changed=MZ_TARGET_TRIPLE_OVERRIDE");
+    let target_triple = match env::var("MZ_TARGET_TRIPLE_OVERRIDE") {
+        Ok(triple) => triple,
+        Err(_) => env::var("TARGET")?,
+    };
+    println!("cargo:rustc-env=TARGET_TRIPLE={}", target_triple);
+
+    println!("cargo:rerun-if-env-changed=MZ_DEV_BUILD_SHA");
+    println!("cargo:rerun-if-env-changed=MZ_GIT_SHA");
+    if let Some(git_dir) = git(&["rev-parse", "--git-dir"]) {
+        // Commits update the branch that `HEAD` refers to rather than `HEAD`
+        // itself, so watch both.
+        let git_dir = PathBuf::from(git_dir);
+        let head = git_dir.join("HEAD");
+        println!("cargo:rerun-if-changed={}", head.display());
+        if let Ok(contents) = fs::read_to_string(&head) {
+            if let Some(branch) = contents.trim().strip_prefix("ref: ") {
+                println!("cargo:rerun-if-changed={}", git_dir.join(branch).display());
+            }
+        }
+    }
+    // An explicit SHA takes precedence over that of the checkout, if any, as
+    // packagers may build from a tree that is not the commit they release.
+    let sha = env::var("MZ_DEV_BUILD_SHA")
+        .or_else(|_| env::var("MZ_GIT_SHA"))
+        .ok()
+        .or_else(|| git(&["rev-parse", "--verify", "HEAD"]));
+    let sha = match sha {
+        Some(sha) => sha,
+        None if env::var("PROFILE")? == "debug" => {
+            println!(
+                "cargo:warning=unable to determine Git SHA; reporting {}",
+                UNKNOWN_SHA
+            );
+            UNKNOWN_SHA.into()
+        }
+        None => {
+            return Err(
+                "unable to determine Git SHA; either build from a working Git clone \
+                 (see https://materialize.com/docs/install/#build-from-source), or specify \
+                 the SHA in the MZ_GIT_SHA environment variable"
+                    .into(),
+            )
+        }
+    };
+    println!("cargo:rustc-env=MZ_GIT_SHA={}", sha);
+    // The build time is that of the last run of this script, which Cargo
+    // repeats only when the directives above call for it, and not on every
+    // build. It is then stale in builds that change only other sources.
+    println!(
+        "cargo:rustc-env=MZ_BUILD_TIME={}",
+        utc_timestamp(SystemTime::now())
+    );
     Ok(())
 }
+
+/// Runs `git` with `args`, returning its trimmed output if it succeeds.
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let output = String::from_utf8(output.stdout).ok()?;
+    Some(output.trim().into())
+}
+
+/// Formats `time` as an ISO 8601 timestamp in UTC, e.g. `2020-12-01T12:34:56Z`.
+fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (days, secs) = ((secs / 86_400) as i64, secs % 86_400);
+    // Converts days since the epoch to a civil date, per Howard Hinnant's
+    // `civil_from_days` algorithm.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        secs % 3_600 / 60,
+        secs % 60
+    )
+}
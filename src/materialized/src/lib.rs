@@ -20,7 +20,6 @@ use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
 use anyhow::anyhow;
-use compile_time_run::run_command_str;
 use futures::channel::mpsc;
 use futures::StreamExt;
 use openssl::ssl::{SslAcceptor, SslFiletype, SslMethod};
@@ -52,27 +51,16 @@ mod version_check;
 #[global_allocator]
 static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
+/// The build information of this binary, as determined by `build.rs`.
+///
+/// The time is when `build.rs` last ran, which is not necessarily when the
+/// binary was last built: Cargo reruns it only on changes to `build.rs`, to
+/// the environment variables that select the SHA or target triple, or to the
+/// Git `HEAD`.
 pub const BUILD_INFO: BuildInfo = BuildInfo {
     version: env!("CARGO_PKG_VERSION"),
-    sha: run_command_str!(
-        "sh",
-        "-c",
-        r#"if [ -n "$MZ_DEV_BUILD_SHA" ]; then
-            echo "$MZ_DEV_BUILD_SHA"
-        else
-            # Unfortunately we need to suppress error messages from `git`, as
-            # run_command_str will display no error message at all if we print
-            # more than one line of output to stderr.
-            git rev-parse --verify HEAD 2>/dev/null || {
-                printf "error: unable to determine Git SHA; " >&2
-                printf "either build from working Git clone " >&2
-                printf "(see https://materialize.com/docs/install/#build-from-source), " >&2
-                printf "or specify SHA manually by setting MZ_DEV_BUILD_SHA environment variable" >&2
-                exit 1
-            }
-        fi"#
-    ),
-    time: run_command_str!("date", "-u", "+%Y-%m-%dT%H:%M:%SZ"),
+    sha: env!("MZ_GIT_SHA"),
+    time: env!("MZ_BUILD_TIME"),
     target_triple: env!("TARGET_TRIPLE"),
 };
 
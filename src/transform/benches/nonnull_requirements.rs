@@ -0,0 +1,59 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use expr::{GlobalId, RelationExpr, ScalarExpr, UnaryFunc};
+use repr::{RelationType, ScalarType};
+use transform::nonnull_requirements::NonNullRequirements;
+use transform::{Transform, TransformArgs};
+
+/// A left-deep join of `depth` joins, each of the prior joins with a source
+/// on their first columns, beneath a filter that requires the first column.
+fn nested_joins(depth: usize) -> RelationExpr {
+    let typ = RelationType::new(vec![ScalarType::Int64.nullable(true); 2]);
+    let mut relation = RelationExpr::global_get(GlobalId::User(0), typ.clone());
+    for id in 1..=depth {
+        let input = RelationExpr::global_get(GlobalId::User(id as u64), typ.clone());
+        relation = RelationExpr::join(vec![relation, input], vec![vec![(0, 0), (1, 0)]]);
+    }
+    relation.filter(vec![ScalarExpr::column(0)
+        .call_unary(UnaryFunc::IsNull)
+        .call_unary(UnaryFunc::Not)])
+}
+
+fn bench_nested_joins(c: &mut Criterion) {
+    let mut group = c.benchmark_group("nonnull_requirements nested joins");
+    for depth in &[16, 64, 256] {
+        let relation = nested_joins(*depth);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(depth),
+            &relation,
+            |b, relation| {
+                b.iter_with_setup(
+                    || relation.clone(),
+                    |mut relation| {
+                        NonNullRequirements::default()
+                            .transform(
+                                &mut relation,
+                                TransformArgs::new(&mut Default::default(), &HashMap::new()),
+                            )
+                            .unwrap()
+                    },
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_nested_joins);
+criterion_main!(benches);
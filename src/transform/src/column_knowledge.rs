@@ -17,7 +17,7 @@ use expr::{RelationExpr, ScalarExpr, UnaryFunc};
 use repr::Datum;
 use repr::{ColumnType, RelationType, ScalarType};
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 
 /// Harvest and act upon per-column information.
 #[derive(Debug)]
@@ -27,26 +27,40 @@ impl crate::Transform for ColumnKnowledge {
     fn transform(
         &self,
         expr: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(expr, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        expr: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
         self.transform(expr)
     }
 }
 
 impl ColumnKnowledge {
-    /// Transforms an expression through accumulated knowledge.
-    pub fn transform(&self, expr: &mut RelationExpr) -> Result<(), crate::TransformError> {
-        ColumnKnowledge::harvest(expr, &mut HashMap::new())?;
-        Ok(())
+    /// Transforms an expression through accumulated knowledge, reporting
+    /// whether it changed the expression.
+    pub fn transform(&self, expr: &mut RelationExpr) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
+        ColumnKnowledge::harvest(expr, &mut HashMap::new(), &mut changed)?;
+        Ok(changed)
     }
 
-    /// Harvest per-column knowledge.
+    /// Harvest per-column knowledge, setting `changed` if it optimizes any
+    /// expression.
     fn harvest(
         expr: &mut RelationExpr,
         knowledge: &mut HashMap<expr::Id, Vec<DatumKnowledge>>,
+        changed: &mut bool,
     ) -> Result<Vec<DatumKnowledge>, crate::TransformError> {
         Ok(match expr {
-            RelationExpr::ArrangeBy { input, .. } => ColumnKnowledge::harvest(input, knowledge)?,
+            RelationExpr::ArrangeBy { input, .. } => {
+                ColumnKnowledge::harvest(input, knowledge, changed)?
+            }
             RelationExpr::Get { id, typ } => knowledge
                 .get(id)
                 .cloned()
@@ -68,10 +82,10 @@ impl ColumnKnowledge {
                 }
             }
             RelationExpr::Let { id, value, body } => {
-                let value_knowledge = ColumnKnowledge::harvest(value, knowledge)?;
+                let value_knowledge = ColumnKnowledge::harvest(value, knowledge, changed)?;
                 let prior_knowledge =
                     knowledge.insert(expr::Id::Local(id.clone()), value_knowledge);
-                let body_knowledge = ColumnKnowledge::harvest(body, knowledge)?;
+                let body_knowledge = ColumnKnowledge::harvest(body, knowledge, changed)?;
                 knowledge.remove(&expr::Id::Local(id.clone()));
                 if let Some(prior_knowledge) = prior_knowledge {
                     knowledge.insert(expr::Id::Local(id.clone()), prior_knowledge);
@@ -79,16 +93,16 @@ impl ColumnKnowledge {
                 body_knowledge
             }
             RelationExpr::Project { input, outputs } => {
-                let input_knowledge = ColumnKnowledge::harvest(input, knowledge)?;
+                let input_knowledge = ColumnKnowledge::harvest(input, knowledge, changed)?;
                 outputs
                     .iter()
                     .map(|i| input_knowledge[*i].clone())
                     .collect()
             }
             RelationExpr::Map { input, scalars } => {
-                let mut input_knowledge = ColumnKnowledge::harvest(input, knowledge)?;
+                let mut input_knowledge = ColumnKnowledge::harvest(input, knowledge, changed)?;
                 for scalar in scalars.iter_mut() {
-                    let know = optimize(scalar, &input.typ(), &input_knowledge[..])?;
+                    let know = optimize(scalar, &input.typ(), &input_knowledge[..], changed)?;
                     input_knowledge.push(know);
                 }
                 input_knowledge
@@ -99,18 +113,18 @@ impl ColumnKnowledge {
                 exprs,
                 demand: _,
             } => {
-                let mut input_knowledge = ColumnKnowledge::harvest(input, knowledge)?;
+                let mut input_knowledge = ColumnKnowledge::harvest(input, knowledge, changed)?;
                 for expr in exprs {
-                    optimize(expr, &input.typ(), &input_knowledge[..])?;
+                    optimize(expr, &input.typ(), &input_knowledge[..], changed)?;
                 }
                 let func_typ = func.output_type();
                 input_knowledge.extend(func_typ.column_types.iter().map(DatumKnowledge::from));
                 input_knowledge
             }
             RelationExpr::Filter { input, predicates } => {
-                let mut input_knowledge = ColumnKnowledge::harvest(input, knowledge)?;
+                let mut input_knowledge = ColumnKnowledge::harvest(input, knowledge, changed)?;
                 for predicate in predicates.iter_mut() {
-                    optimize(predicate, &input.typ(), &input_knowledge[..])?;
+                    optimize(predicate, &input.typ(), &input_knowledge[..], changed)?;
                 }
                 // If any predicate tests a column for equality, truth, or is_null, we learn stuff.
                 for predicate in predicates.iter() {
@@ -164,7 +178,7 @@ impl ColumnKnowledge {
             } => {
                 let mut knowledges = Vec::new();
                 for input in inputs.iter_mut() {
-                    for knowledge in ColumnKnowledge::harvest(input, knowledge)? {
+                    for knowledge in ColumnKnowledge::harvest(input, knowledge, changed)? {
                         knowledges.push(knowledge);
                     }
                 }
@@ -195,15 +209,19 @@ impl ColumnKnowledge {
                 monotonic: _,
                 expected_group_size: _,
             } => {
-                let input_knowledge = ColumnKnowledge::harvest(input, knowledge)?;
+                let input_knowledge = ColumnKnowledge::harvest(input, knowledge, changed)?;
                 let mut output = group_key
                     .iter_mut()
-                    .map(|k| optimize(k, &input.typ(), &input_knowledge[..]))
+                    .map(|k| optimize(k, &input.typ(), &input_knowledge[..], changed))
                     .collect::<Result<Vec<_>, _>>()?;
                 for aggregate in aggregates.iter_mut() {
                     use expr::AggregateFunc;
-                    let knowledge =
-                        optimize(&mut aggregate.expr, &input.typ(), &input_knowledge[..])?;
+                    let knowledge = optimize(
+                        &mut aggregate.expr,
+                        &input.typ(),
+                        &input_knowledge[..],
+                        changed,
+                    )?;
                     // This could be improved.
                     let knowledge = match aggregate.func {
                         AggregateFunc::MaxInt32
@@ -243,15 +261,19 @@ impl ColumnKnowledge {
                 }
                 output
             }
-            RelationExpr::TopK { input, .. } => ColumnKnowledge::harvest(input, knowledge)?,
-            RelationExpr::Negate { input } => ColumnKnowledge::harvest(input, knowledge)?,
-            RelationExpr::Threshold { input } => ColumnKnowledge::harvest(input, knowledge)?,
+            RelationExpr::TopK { input, .. } => {
+                ColumnKnowledge::harvest(input, knowledge, changed)?
+            }
+            RelationExpr::Negate { input } => ColumnKnowledge::harvest(input, knowledge, changed)?,
+            RelationExpr::Threshold { input } => {
+                ColumnKnowledge::harvest(input, knowledge, changed)?
+            }
             RelationExpr::Union { base, inputs } => {
-                let mut know = ColumnKnowledge::harvest(base, knowledge)?;
+                let mut know = ColumnKnowledge::harvest(base, knowledge, changed)?;
                 for input in inputs {
                     know = know
                         .into_iter()
-                        .zip_eq(ColumnKnowledge::harvest(input, knowledge)?)
+                        .zip_eq(ColumnKnowledge::harvest(input, knowledge, changed)?)
                         .map(|(k1, k2)| DatumKnowledge {
                             value: if k1.value == k2.value {
                                 k1.value.clone()
@@ -319,16 +341,20 @@ impl From<&ColumnType> for DatumKnowledge {
 }
 
 /// Attempts to optimize
+///
+/// Sets `changed` if it rewrites `expr`.
 pub fn optimize(
     expr: &mut ScalarExpr,
     input_type: &RelationType,
     column_knowledge: &[DatumKnowledge],
+    changed: &mut bool,
 ) -> Result<DatumKnowledge, crate::TransformError> {
     Ok(match expr {
         ScalarExpr::Column(index) => {
             let index = *index;
             if let Some((datum, typ)) = &column_knowledge[index].value {
                 *expr = ScalarExpr::Literal(Ok(datum.clone()), typ.clone());
+                *changed = true;
             }
             column_knowledge[index].clone()
         }
@@ -344,16 +370,19 @@ pub fn optimize(
         }
         ScalarExpr::CallNullary(_) => {
             expr.reduce(input_type);
-            optimize(expr, input_type, column_knowledge)?
+            *changed = true;
+            optimize(expr, input_type, column_knowledge, changed)?
         }
         ScalarExpr::CallUnary { func, expr: inner } => {
-            let knowledge = optimize(inner, input_type, column_knowledge)?;
+            let knowledge = optimize(inner, input_type, column_knowledge, changed)?;
             if knowledge.value.is_some() {
                 expr.reduce(input_type);
-                optimize(expr, input_type, column_knowledge)?
+                *changed = true;
+                optimize(expr, input_type, column_knowledge, changed)?
             } else if func == &UnaryFunc::IsNull && !knowledge.nullable {
                 *expr = ScalarExpr::literal_ok(Datum::False, ScalarType::Bool.nullable(false));
-                optimize(expr, input_type, column_knowledge)?
+                *changed = true;
+                optimize(expr, input_type, column_knowledge, changed)?
             } else {
                 DatumKnowledge::default()
             }
@@ -363,11 +392,12 @@ pub fn optimize(
             expr1,
             expr2,
         } => {
-            let knowledge1 = optimize(expr1, input_type, column_knowledge)?;
-            let knowledge2 = optimize(expr2, input_type, column_knowledge)?;
+            let knowledge1 = optimize(expr1, input_type, column_knowledge, changed)?;
+            let knowledge2 = optimize(expr2, input_type, column_knowledge, changed)?;
             if knowledge1.value.is_some() && knowledge2.value.is_some() {
                 expr.reduce(input_type);
-                optimize(expr, input_type, column_knowledge)?
+                *changed = true;
+                optimize(expr, input_type, column_knowledge, changed)?
             } else {
                 DatumKnowledge::default()
             }
@@ -375,24 +405,28 @@ pub fn optimize(
         ScalarExpr::CallVariadic { func: _, exprs } => {
             let mut knows = Vec::new();
             for expr in exprs.iter_mut() {
-                knows.push(optimize(expr, input_type, column_knowledge)?);
+                knows.push(optimize(expr, input_type, column_knowledge, changed)?);
             }
 
             if knows.iter().all(|k| k.value.is_some()) {
                 expr.reduce(input_type);
-                optimize(expr, input_type, column_knowledge)?
+                *changed = true;
+                optimize(expr, input_type, column_knowledge, changed)?
             } else {
                 DatumKnowledge::default()
             }
         }
         ScalarExpr::If { cond, then, els } => {
-            if let Some((value, _typ)) = optimize(cond, input_type, column_knowledge)?.value {
+            if let Some((value, _typ)) =
+                optimize(cond, input_type, column_knowledge, changed)?.value
+            {
                 match value.unpack_first() {
                     Datum::True => *expr = (**then).clone(),
                     Datum::False | Datum::Null => *expr = (**els).clone(),
                     d => panic!("IF condition evaluated to non-boolean datum {:?}", d),
                 }
-                optimize(expr, input_type, column_knowledge)?
+                *changed = true;
+                optimize(expr, input_type, column_knowledge, changed)?
             } else {
                 DatumKnowledge::default()
             }
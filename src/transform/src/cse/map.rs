@@ -19,7 +19,7 @@
 //! busywork and less efficiency, but the wins can be substantial when
 //! expressions re-use complex subexpressions.
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 use expr::{RelationExpr, ScalarExpr};
 
 /// Performs common sub-expression elimination.
@@ -30,30 +30,45 @@ impl crate::Transform for Map {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl Map {
-    /// Performs common sub-expression elimination.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Performs common sub-expression elimination, reporting whether it
+    /// changed `relation`.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
         if let RelationExpr::Map { input, scalars } = relation {
             let input_arity = input.arity();
             let scalars_len = scalars.len();
+            let original = scalars.clone();
             let (scalars, projection) = memoize_and_reuse(scalars, input_arity);
+            let mut changed = scalars != original;
             let mut expression = input.take_dangerous().map(scalars);
             if projection.len() != (input_arity + scalars_len)
                 || projection.iter().enumerate().any(|(a, b)| a != *b)
             {
                 expression = expression.project(projection);
+                changed = true;
             }
             *relation = expression;
+            return changed;
         }
+        false
     }
 }
 
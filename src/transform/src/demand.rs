@@ -14,7 +14,7 @@ use std::collections::{HashMap, HashSet};
 use expr::{AggregateExpr, AggregateFunc, Id, JoinInputMapper, RelationExpr, ScalarExpr};
 use repr::Datum;
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 
 /// Drive demand from the root through operators.
 ///
@@ -35,26 +35,34 @@ impl crate::Transform for Demand {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
-        self.action(
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        Ok(self.action(
             relation,
             (0..relation.typ().column_types.len()).collect(),
             &mut HashMap::new(),
-        );
-        Ok(())
+        ))
     }
 }
 
 impl Demand {
-    /// Columns to be produced.
+    /// Columns to be produced, reporting whether it changed `relation`.
     pub fn action(
         &self,
         relation: &mut RelationExpr,
         mut columns: HashSet<usize>,
         gets: &mut HashMap<Id, HashSet<usize>>,
-    ) {
+    ) -> bool {
         let relation_type = relation.typ();
+        let mut changed = false;
         match relation {
             RelationExpr::Constant { .. } => {
                 // Nothing clever to do with constants, that I can think of.
@@ -67,16 +75,16 @@ impl Demand {
                 // and pushes the union of the requirements at its value.
                 let id = Id::Local(*id);
                 let prior = gets.insert(id, HashSet::new());
-                self.action(body, columns, gets);
+                changed |= self.action(body, columns, gets);
                 let needs = gets.remove(&id).unwrap();
                 if let Some(prior) = prior {
                     gets.insert(id, prior);
                 }
 
-                self.action(value, needs, gets);
+                changed |= self.action(value, needs, gets);
             }
             RelationExpr::Project { input, outputs } => {
-                self.action(
+                changed |= self.action(
                     input,
                     columns.into_iter().map(|c| outputs[c]).collect(),
                     gets,
@@ -108,12 +116,13 @@ impl Demand {
                             let typ = relation_type.column_types[arity + index].clone();
                             *scalar =
                                 ScalarExpr::Literal(Ok(row_packer.pack(Some(Datum::Dummy))), typ);
+                            changed = true;
                         }
                     }
                 }
 
                 columns.retain(|c| *c < arity);
-                self.action(input, columns, gets);
+                changed |= self.action(input, columns, gets);
             }
             RelationExpr::FlatMap {
                 input,
@@ -123,6 +132,7 @@ impl Demand {
             } => {
                 let mut sorted = columns.iter().cloned().collect::<Vec<_>>();
                 sorted.sort_unstable();
+                changed |= demand.as_ref() != Some(&sorted);
                 *demand = Some(sorted);
                 // A FlatMap which returns zero rows acts like a filter
                 // so we always need to execute it
@@ -130,7 +140,7 @@ impl Demand {
                     columns.extend(expr.support());
                 }
                 columns.retain(|c| *c < input.arity());
-                self.action(input, columns, gets);
+                changed |= self.action(input, columns, gets);
             }
             RelationExpr::Filter { input, predicates } => {
                 for predicate in predicates {
@@ -138,7 +148,7 @@ impl Demand {
                         columns.insert(column);
                     }
                 }
-                self.action(input, columns, gets);
+                changed |= self.action(input, columns, gets);
             }
             RelationExpr::Join {
                 inputs,
@@ -171,6 +181,7 @@ impl Demand {
                 // when an externally demanded column will be replaced with a copy of another.
                 let mut demand_vec = columns.iter().map(|c| permutation[*c]).collect::<Vec<_>>();
                 demand_vec.sort_unstable();
+                changed |= demand.as_ref() != Some(&demand_vec);
                 *demand = Some(demand_vec);
                 let should_permute = columns.iter().any(|c| permutation[*c] != *c);
 
@@ -186,13 +197,14 @@ impl Demand {
 
                 // Recursively indicate the requirements.
                 for (input, columns) in inputs.iter_mut().zip(new_columns) {
-                    self.action(input, columns, gets);
+                    changed |= self.action(input, columns, gets);
                 }
 
                 // Install a permutation if any demanded column is not the
                 // canonical column.
                 if should_permute {
                     *relation = relation.take_dangerous().project(permutation);
+                    changed = true;
                 }
             }
             RelationExpr::Reduce {
@@ -220,15 +232,17 @@ impl Demand {
                 for index in (0..aggregates.len()).rev() {
                     if !columns.contains(&(group_key.len() + index)) {
                         let typ = aggregates[index].typ(&input_type);
-                        aggregates[index] = AggregateExpr {
+                        let dummy = AggregateExpr {
                             func: AggregateFunc::Dummy,
                             expr: ScalarExpr::literal_ok(Datum::Dummy, typ),
                             distinct: false,
                         };
+                        changed |= aggregates[index] != dummy;
+                        aggregates[index] = dummy;
                     }
                 }
 
-                self.action(input, new_columns, gets);
+                changed |= self.action(input, new_columns, gets);
             }
             RelationExpr::TopK {
                 input,
@@ -240,22 +254,22 @@ impl Demand {
                 // which rows are retained.
                 columns.extend(group_key.iter().cloned());
                 columns.extend(order_key.iter().map(|o| o.column));
-                self.action(input, columns, gets);
+                changed |= self.action(input, columns, gets);
             }
             RelationExpr::Negate { input } => {
-                self.action(input, columns, gets);
+                changed |= self.action(input, columns, gets);
             }
             RelationExpr::Threshold { input } => {
                 // Threshold requires all columns, as collapsing any distinct values
                 // has the potential to change how it thresholds counts. This could
                 // be improved with reasoning about distinctness or non-negativity.
                 let arity = input.arity();
-                self.action(input, (0..arity).collect(), gets);
+                changed |= self.action(input, (0..arity).collect(), gets);
             }
             RelationExpr::Union { base, inputs } => {
-                self.action(base, columns.clone(), gets);
+                changed |= self.action(base, columns.clone(), gets);
                 for input in inputs {
-                    self.action(input, columns.clone(), gets);
+                    changed |= self.action(input, columns.clone(), gets);
                 }
             }
             RelationExpr::ArrangeBy { input, keys } => {
@@ -264,8 +278,9 @@ impl Demand {
                         columns.extend(key.support());
                     }
                 }
-                self.action(input, columns, gets);
+                changed |= self.action(input, columns, gets);
             }
         }
+        changed
     }
 }
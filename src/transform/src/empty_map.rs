@@ -9,7 +9,7 @@
 
 //! Remove empty `Map` operators.
 
-use crate::{RelationExpr, TransformArgs};
+use crate::{RelationExpr, Transform, TransformArgs};
 
 /// Remove empty `Map` operators.
 #[derive(Debug)]
@@ -19,22 +19,33 @@ impl crate::Transform for EmptyMap {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut_pre(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl EmptyMap {
-    /// Remove empty `Map` operators.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Remove empty `Map` operators, reporting whether `relation` was one.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
         if let RelationExpr::Map { input, scalars } = relation {
             if scalars.is_empty() {
                 *relation = input.take_dangerous();
+                return true;
             }
         }
+        false
     }
 }
@@ -32,17 +32,17 @@
 //!
 //! // .transform() will deduplicate any predicates
 //! use transform::{Transform, TransformArgs};
-//! Filter.transform(&mut expr, TransformArgs {
-//!   id_gen: &mut Default::default(),
-//!   indexes: &std::collections::HashMap::new(),
-//! });
+//! Filter.transform(
+//!     &mut expr,
+//!     TransformArgs::new(&mut Default::default(), &std::collections::HashMap::new()),
+//! );
 //!
 //! let correct = input.filter(vec![predicate0]);
 //!
 //! assert_eq!(expr, correct);
 //! ```
 
-use crate::{RelationExpr, ScalarExpr, TransformArgs};
+use crate::{RelationExpr, ScalarExpr, Transform, TransformArgs};
 
 /// Fuses multiple `Filter` operators into one and deduplicates predicates.
 #[derive(Debug)]
@@ -52,18 +52,29 @@ impl crate::Transform for Filter {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut_pre(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl Filter {
-    /// Fuses multiple `Filter` operators into one and deduplicates predicates.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Fuses multiple `Filter` operators into one and deduplicates predicates,
+    /// reporting whether it changed `relation`.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
+        let mut changed = false;
         if let RelationExpr::Filter { input, predicates } = relation {
             // consolidate nested filters.
             while let RelationExpr::Filter {
@@ -73,24 +84,32 @@ impl Filter {
             {
                 predicates.extend(p2.drain(..));
                 *input = Box::new(inner.take_dangerous());
+                changed = true;
             }
 
             for predicate in predicates.iter_mut() {
-                canonicalize_predicate(predicate);
+                changed |= canonicalize_predicate(predicate);
             }
+            let len = predicates.len();
+            changed |= predicates.windows(2).any(|pair| pair[0] > pair[1]);
             predicates.sort();
             predicates.dedup();
+            changed |= predicates.len() != len;
 
             // remove the Filter stage if empty.
             if predicates.is_empty() {
                 *relation = input.take_dangerous();
+                changed = true;
             }
         }
+        changed
     }
 }
 
-/// Ensures that two equalities are made in a consistent order.
-fn canonicalize_predicate(predicate: &mut ScalarExpr) {
+/// Ensures that two equalities are made in a consistent order, reporting
+/// whether `predicate` changed.
+fn canonicalize_predicate(predicate: &mut ScalarExpr) -> bool {
+    let mut changed = false;
     if let ScalarExpr::CallBinary {
         func: expr::BinaryFunc::Eq,
         expr1,
@@ -100,6 +119,7 @@ fn canonicalize_predicate(predicate: &mut ScalarExpr) {
         // Canonically order elements so that deduplication works better.
         if expr2 < expr1 {
             ::std::mem::swap(expr1, expr2);
+            changed = true;
         }
 
         // Comparison to self is always true unless the element is `Datum::Null`.
@@ -108,6 +128,8 @@ fn canonicalize_predicate(predicate: &mut ScalarExpr) {
                 .clone()
                 .call_unary(expr::UnaryFunc::IsNull)
                 .call_unary(expr::UnaryFunc::Not);
+            changed = true;
         }
     }
+    changed
 }
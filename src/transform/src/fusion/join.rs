@@ -15,7 +15,7 @@
 //! our ability to plan these joins, and reason about other operators motion
 //! aroud them.
 
-use crate::{RelationExpr, ScalarExpr, TransformArgs};
+use crate::{RelationExpr, ScalarExpr, Transform, TransformArgs};
 
 /// Fuses multiple `Join` operators into one `Join` operator.
 #[derive(Debug)]
@@ -25,18 +25,32 @@ impl crate::Transform for Join {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl Join {
-    /// Fuses multiple `Join` operators into one `Join` operator.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Fuses multiple `Join` operators into one `Join` operator, reporting
+    /// whether it changed `relation`.
+    ///
+    /// The demand and implementation of the join are reset, which is a
+    /// change if they were set.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
+        let mut changed = false;
         if let RelationExpr::Join {
             inputs,
             equivalences,
@@ -57,6 +71,7 @@ impl Join {
                     ..
                 } = input
                 {
+                    changed = true;
                     // Update and push all of the variables.
                     for mut equivalence in equivalences.drain(..) {
                         for expr in equivalence.iter_mut() {
@@ -84,7 +99,9 @@ impl Join {
 
             *inputs = new_inputs;
             *equivalences = new_equivalences;
+            changed |= demand.is_some();
             *demand = None;
+            changed |= *implementation != expr::JoinImplementation::Unimplemented;
             *implementation = expr::JoinImplementation::Unimplemented;
 
             // Join variables may not be an equivalence class. Better ensure!
@@ -96,16 +113,23 @@ impl Join {
                     {
                         let to_extend = std::mem::replace(&mut equivalences[index], Vec::new());
                         equivalences[inner].extend(to_extend);
+                        changed = true;
                     }
                 }
             }
+            let len = equivalences.len();
             equivalences.retain(|v| !v.is_empty());
+            changed |= equivalences.len() != len;
 
             // put join constraints in a canonical format.
             for equivalence in equivalences.iter_mut() {
+                let len = equivalence.len();
+                changed |= equivalence.windows(2).any(|pair| pair[0] > pair[1]);
                 equivalence.sort();
                 equivalence.dedup();
+                changed |= equivalence.len() != len;
             }
         }
+        changed
     }
 }
@@ -17,7 +17,7 @@
 
 use std::mem;
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 use expr::RelationExpr;
 
 /// Fuses a sequence of `Map` operators in to one `Map` operator.
@@ -28,18 +28,29 @@ impl crate::Transform for Map {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut_pre(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl Map {
-    /// Fuses a sequence of `Map` operators in to one `Map` operator.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Fuses a sequence of `Map` operators in to one `Map` operator,
+    /// reporting whether it fused any.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
+        let mut changed = false;
         if let RelationExpr::Map { input, scalars } = relation {
             while let RelationExpr::Map {
                 input: inner_input,
@@ -49,7 +60,9 @@ impl Map {
                 inner_scalars.append(scalars);
                 mem::swap(scalars, inner_scalars);
                 **input = inner_input.take_dangerous();
+                changed = true;
             }
         }
+        changed
     }
 }
@@ -11,7 +11,7 @@
 
 // TODO(frank): evaluate for redundancy with projection hoisting.
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 use expr::RelationExpr;
 
 /// Fuses Project operators with parent operators when possible.
@@ -22,18 +22,29 @@ impl crate::Transform for Project {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut_pre(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl Project {
-    /// Fuses Project operators with parent operators when possible.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Fuses Project operators with parent operators when possible,
+    /// reporting whether it fused any.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
+        let mut changed = false;
         if let RelationExpr::Project { input, outputs } = relation {
             while let RelationExpr::Project {
                 input: inner,
@@ -42,9 +53,11 @@ impl Project {
             {
                 *outputs = outputs.iter().map(|i| outputs2[*i]).collect();
                 **input = inner.take_dangerous();
+                changed = true;
             }
             if outputs.iter().enumerate().all(|(a, b)| a == *b) && outputs.len() == input.arity() {
                 *relation = input.take_dangerous();
+                changed = true;
             }
         }
 
@@ -70,7 +83,9 @@ impl Project {
                     aggregate.expr.permute(&outputs[..]);
                 }
                 *input = Box::new(inner.take_dangerous());
+                changed = true;
             }
         }
+        changed
     }
 }
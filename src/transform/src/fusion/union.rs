@@ -11,7 +11,7 @@
 
 use std::iter;
 
-use crate::{RelationExpr, TransformArgs};
+use crate::{RelationExpr, Transform, TransformArgs};
 
 /// Fuses multiple `Union` operators into one.
 #[derive(Debug)]
@@ -21,18 +21,28 @@ impl crate::Transform for Union {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut_pre(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl Union {
-    /// Fuses multiple `Union` operators into one.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Fuses multiple `Union` operators into one, reporting whether it fused
+    /// any.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
         let relation_type = relation.typ();
         if let RelationExpr::Union { base, inputs } = relation {
             let can_fuse = iter::once(&**base)
@@ -50,7 +60,9 @@ impl Union {
                     }
                 }
                 *relation = RelationExpr::union_many(new_inputs, relation_type);
+                return true;
             }
         }
+        false
     }
 }
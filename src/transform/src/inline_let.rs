@@ -14,7 +14,7 @@
 //! `Get` statement in their body. These cases can be inlined without
 //! harming planning.
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 use expr::{Id, LocalId, RelationExpr};
 
 /// Install replace certain `Get` operators with their `Let` value.
@@ -25,10 +25,27 @@ impl crate::Transform for InlineLet {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        // The `Let`s at the root are lifted to where they already are, unless
+        // they are inlined or others are lifted among them.
+        let mut root_lets = vec![];
+        let mut node = &*relation;
+        while let RelationExpr::Let { id, body, .. } = node {
+            root_lets.push(*id);
+            node = body;
+        }
         let mut lets = vec![];
-        self.action(relation, &mut lets);
+        let inlined = self.action(relation, &mut lets);
+        let changed = inlined || lets.iter().map(|(id, _)| *id).ne(root_lets);
         for (id, value) in lets.into_iter().rev() {
             *relation = RelationExpr::Let {
                 id,
@@ -36,15 +53,23 @@ impl crate::Transform for InlineLet {
                 body: Box::new(relation.take_safely()),
             };
         }
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl InlineLet {
-    /// Install replace certain `Get` operators with their `Let` value.
-    pub fn action(&self, relation: &mut RelationExpr, lets: &mut Vec<(LocalId, RelationExpr)>) {
+    /// Install replace certain `Get` operators with their `Let` value,
+    /// reporting whether it inlined any `Let`.
+    ///
+    /// The others are appended to `lets`, to be bound around the result.
+    pub fn action(
+        &self,
+        relation: &mut RelationExpr,
+        lets: &mut Vec<(LocalId, RelationExpr)>,
+    ) -> bool {
+        let mut inlined = false;
         if let RelationExpr::Let { id, value, body } = relation {
-            self.action(value, lets);
+            inlined |= self.action(value, lets);
 
             let mut num_gets = 0;
             body.visit_mut_pre(&mut |relation| match relation {
@@ -66,6 +91,7 @@ impl InlineLet {
                     }
                     _ => (),
                 });
+                inlined = true;
             } else {
                 // otherwise lift it to the top so it's out of the way
                 lets.push((*id, value.take_safely()));
@@ -73,9 +99,10 @@ impl InlineLet {
 
             *relation = body.take_safely();
             // might be another Let in the body so have to recur here
-            self.action(relation, lets);
+            inlined |= self.action(relation, lets);
         } else {
-            relation.visit1_mut(|child| self.action(child, lets));
+            relation.visit1_mut(|child| inlined |= self.action(child, lets));
         }
+        inlined
     }
 }
@@ -15,7 +15,7 @@
 
 use repr::RelationType;
 
-use crate::{RelationExpr, TransformArgs};
+use crate::{RelationExpr, Transform, TransformArgs};
 
 /// Removes unit collections from joins, and joins with fewer than two inputs.
 #[derive(Debug)]
@@ -25,24 +25,35 @@ impl crate::Transform for JoinElision {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl JoinElision {
-    /// Removes unit collections from joins, and joins with fewer than two inputs.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Removes unit collections from joins, and joins with fewer than two inputs,
+    /// reporting whether it removed either.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
         if let RelationExpr::Join {
             inputs,
             equivalences,
             ..
         } = relation
         {
+            let len = inputs.len();
             inputs.retain(|input| {
                 if let RelationExpr::Constant { rows, typ } = &input {
                     !(rows.len() == 1 && typ.column_types.len() == 0 && rows[0].1 == 1)
@@ -57,6 +68,7 @@ impl JoinElision {
                 0 => {
                     // The identity for join is the collection containing a single 0-ary row.
                     *relation = RelationExpr::constant(vec![vec![]], RelationType::empty());
+                    return true;
                 }
                 1 => {
                     // if there are constraints, they probably should have
@@ -64,10 +76,13 @@ impl JoinElision {
                     // not re-write that code here.
                     if equivalences.is_empty() {
                         *relation = inputs.pop().unwrap();
+                        return true;
                     }
                 }
                 _ => {}
             }
+            return inputs.len() != len;
         }
+        false
     }
 }
@@ -18,7 +18,7 @@
 
 use std::collections::HashMap;
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 use expr::{Id, JoinInputMapper, RelationExpr, ScalarExpr};
 
 /// Determines the join implementation for join operators.
@@ -31,13 +31,20 @@ impl crate::Transform for JoinImplementation {
         relation: &mut RelationExpr,
         args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        args: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
         let mut arranged = HashMap::new();
         for (on_id, idxs) in args.indexes {
             let keys = idxs.iter().map(|(_id, keys)| keys.clone()).collect();
             arranged.insert(Id::Global(*on_id), keys);
         }
-        self.action_recursive(relation, &mut arranged);
-        Ok(())
+        Ok(self.action_recursive(relation, &mut arranged))
     }
 }
 
@@ -46,13 +53,16 @@ impl JoinImplementation {
     ///
     /// This method accumulates state about let-bound arrangements, so that
     /// join operators can more accurately assess their available arrangements.
+    ///
+    /// Returns whether it changed the implementation of any join.
     pub fn action_recursive(
         &self,
         relation: &mut RelationExpr,
         arranged: &mut HashMap<Id, Vec<Vec<ScalarExpr>>>,
-    ) {
+    ) -> bool {
+        let mut changed = false;
         if let RelationExpr::Let { id, value, body } = relation {
-            self.action_recursive(value, arranged);
+            changed |= self.action_recursive(value, arranged);
             match &**value {
                 RelationExpr::ArrangeBy { keys, .. } => {
                     arranged.insert(Id::Local(*id), keys.clone());
@@ -65,16 +75,22 @@ impl JoinImplementation {
                 }
                 _ => {}
             }
-            self.action_recursive(body, arranged);
+            changed |= self.action_recursive(body, arranged);
             arranged.remove(&Id::Local(*id));
         } else {
-            relation.visit1_mut(|e| self.action_recursive(e, arranged));
-            self.action(relation, arranged);
+            relation.visit1_mut(|e| changed |= self.action_recursive(e, arranged));
+            changed |= self.action(relation, arranged);
         }
+        changed
     }
 
-    /// Determines the join implementation for join operators.
-    pub fn action(&self, relation: &mut RelationExpr, indexes: &HashMap<Id, Vec<Vec<ScalarExpr>>>) {
+    /// Determines the join implementation for join operators, reporting
+    /// whether it changed `relation`.
+    pub fn action(
+        &self,
+        relation: &mut RelationExpr,
+        indexes: &HashMap<Id, Vec<Vec<ScalarExpr>>>,
+    ) -> bool {
         if let RelationExpr::Join {
             inputs,
             equivalences,
@@ -164,10 +180,17 @@ impl JoinImplementation {
                 &unique_keys,
             );
 
-            *relation = delta_query_plan
+            let plan = delta_query_plan
                 .or(differential_plan)
                 .expect("Failed to produce a join plan");
+            // Each plan is built from a copy of the join, so comparing with
+            // it costs no more than planning did.
+            if plan != *relation {
+                *relation = plan;
+                return true;
+            }
         }
+        false
     }
 }
 
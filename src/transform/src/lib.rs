@@ -21,9 +21,10 @@
 #![forbid(missing_docs)]
 #![deny(missing_debug_implementations)]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fmt;
+use std::time::Instant;
 
 use expr::{EvalError, GlobalId, IdGen, OptimizedRelationExpr, RelationExpr, ScalarExpr};
 
@@ -38,6 +39,7 @@ pub mod join_implementation;
 pub mod map_lifting;
 pub mod nonnull_requirements;
 pub mod nonnullable;
+pub mod null_filter_elision;
 pub mod predicate_pushdown;
 pub mod projection_extraction;
 pub mod projection_lifting;
@@ -46,10 +48,15 @@ pub mod reduction;
 pub mod reduction_pushdown;
 pub mod redundant_join;
 pub mod split_predicates;
+pub mod testing;
 pub mod topk_elision;
 pub mod update_let;
 // pub mod use_indexes;
 
+#[cfg(test)]
+mod test_util;
+mod util;
+
 pub mod dataflow;
 pub use dataflow::optimize_dataflow;
 
@@ -60,6 +67,128 @@ pub struct TransformArgs<'a> {
     pub id_gen: &'a mut IdGen,
     /// The indexes accessible.
     pub indexes: &'a HashMap<GlobalId, Vec<(GlobalId, Vec<ScalarExpr>)>>,
+    /// Whether to log the name of each transform before and after it runs.
+    pub trace: bool,
+    /// The number of steps a transform may take before giving up, if bounded.
+    ///
+    /// Transforms that iterate or recurse should consume this with
+    /// `consume_fuel` and propagate the `TransformError::Exhausted` it returns.
+    pub fuel: Option<usize>,
+    /// Where to record statistics about the changes transforms make, if anywhere.
+    pub stats: Option<&'a mut TransformStats>,
+    /// Where to record the columns of each source that must be non-null, if
+    /// anywhere.
+    pub source_requirements: Option<&'a mut SourceRequirementReport>,
+    /// The instant by which a transform must complete, if bounded.
+    ///
+    /// Transforms that iterate or recurse should check this with
+    /// `check_deadline` and propagate the `TransformError::Timeout` it returns.
+    pub deadline: Option<Instant>,
+}
+
+impl<'a> TransformArgs<'a> {
+    /// Arguments with the given `id_gen` and `indexes`, that neither trace
+    /// nor bound transforms, and record nothing.
+    ///
+    /// Other arguments may be set with struct update syntax, e.g.
+    /// `TransformArgs { fuel: Some(100), ..TransformArgs::new(id_gen, indexes) }`.
+    pub fn new(
+        id_gen: &'a mut IdGen,
+        indexes: &'a HashMap<GlobalId, Vec<(GlobalId, Vec<ScalarExpr>)>>,
+    ) -> Self {
+        TransformArgs {
+            id_gen,
+            indexes,
+            trace: false,
+            fuel: None,
+            stats: None,
+            source_requirements: None,
+            deadline: None,
+        }
+    }
+}
+
+/// Statistics about the changes transforms make to a relation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransformStats {
+    /// The number of rows removed from `Constant` collections.
+    pub constant_rows_pruned: usize,
+    /// The number of expressions replaced by empty collections.
+    pub branches_zeroed: usize,
+    /// The number of non-empty `Constant` collections from which each row
+    /// was removed.
+    pub constants_emptied: usize,
+}
+
+/// The columns of each global source that a plan requires to be non-null.
+///
+/// Columns listed here could be declared `NOT NULL`, or indexed, without
+/// changing the results of the plan. Operators may use this to advise users
+/// on their sources.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceRequirementReport {
+    /// The columns required non-null, keyed by the source that has them.
+    pub sources: HashMap<GlobalId, HashSet<usize>>,
+    /// The arrangements of each relation reported, in post-order, with
+    /// whether each of their keys is proven non-null.
+    pub arrangements: Vec<ArrangementNullability>,
+}
+
+/// Whether each key of an arrangement is proven non-null.
+///
+/// Joins equate null keys, as `Datum::eq` does, and so an arrangement keeps
+/// its rows with null keys. A key is reported non-null only where that has
+/// been proven upstream of the arrangement, by types, filters or join
+/// equivalences, and only such keys may be assumed to have no nulls.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArrangementNullability {
+    /// The keys of the arrangement.
+    pub keys: Vec<Vec<ScalarExpr>>,
+    /// For each key, whether each of its expressions is proven non-null.
+    pub non_null: Vec<bool>,
+}
+
+impl SourceRequirementReport {
+    /// Records that the columns `columns` of `id` must be non-null.
+    ///
+    /// If `id` has requirements from an earlier record, e.g. from another
+    /// relation optimized with the same report, only the columns required by
+    /// both are kept.
+    pub fn record(&mut self, id: GlobalId, columns: HashSet<usize>) {
+        match self.sources.get_mut(&id) {
+            Some(existing) => {
+                *existing = util::intersect_column_sets(vec![std::mem::take(existing), columns])
+            }
+            None => {
+                self.sources.insert(id, columns);
+            }
+        }
+    }
+}
+
+/// Consumes one unit of `fuel`, failing if none remains.
+///
+/// An unset `fuel` is unbounded and never runs out.
+pub fn consume_fuel(fuel: &mut Option<usize>) -> Result<(), TransformError> {
+    match fuel {
+        Some(0) => Err(TransformError::Exhausted),
+        Some(remaining) => {
+            *remaining -= 1;
+            Ok(())
+        }
+        None => Ok(()),
+    }
+}
+
+/// Fails if `deadline` has passed.
+///
+/// An unset `deadline` never passes. Reading the clock costs more than
+/// consuming fuel, and so transforms need not check it at every step.
+pub fn check_deadline(deadline: Option<Instant>) -> Result<(), TransformError> {
+    match deadline {
+        Some(deadline) if Instant::now() >= deadline => Err(TransformError::Timeout),
+        _ => Ok(()),
+    }
 }
 
 /// Types capable of transforming relation expressions.
@@ -70,6 +199,92 @@ pub trait Transform: std::fmt::Debug {
         relation: &mut RelationExpr,
         args: TransformArgs,
     ) -> Result<(), TransformError>;
+
+    /// Transform a relation as `transform` does, reporting whether the
+    /// relation may have changed.
+    ///
+    /// The default implementation reports a change whatever the transform
+    /// did, which leaves `Fixpoint` to compare the relation to its value at
+    /// the start of the round. Transforms that track their own changes
+    /// should override it, so that a round of transforms that report none
+    /// can end without the comparison.
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        args: TransformArgs,
+    ) -> Result<bool, TransformError> {
+        self.transform(relation, args)?;
+        Ok(true)
+    }
+
+    /// A name identifying the transform, for use in optimizer traces.
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+    }
+
+    /// The names of transforms that must run before this one, if they are to
+    /// run at all.
+    fn requires(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// The names of analyses whose results this transform may invalidate, and
+    /// which must be recomputed after it runs.
+    fn invalidates(&self) -> &'static [&'static str] {
+        &[]
+    }
+}
+
+/// Orders `transforms` so that each runs after the transforms it requires,
+/// and otherwise in the order given.
+///
+/// Requirements on transforms that are not among `transforms` are ignored.
+/// Transforms that require one another, directly or not, cannot be ordered
+/// and result in an error.
+pub fn schedule(
+    mut transforms: Vec<Box<dyn Transform + Send>>,
+) -> Result<Vec<Box<dyn Transform + Send>>, TransformError> {
+    let mut scheduled = Vec::with_capacity(transforms.len());
+    while !transforms.is_empty() {
+        let ready = transforms.iter().position(|transform| {
+            transform.requires().iter().all(|required| {
+                transforms
+                    .iter()
+                    .all(|other| other.name() == transform.name() || other.name() != *required)
+            })
+        });
+        match ready {
+            Some(index) => scheduled.push(transforms.remove(index)),
+            None => {
+                return Err(TransformError::Internal(format!(
+                    "cyclic transform requirements among {}",
+                    transforms
+                        .iter()
+                        .map(|transform| transform.name())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )))
+            }
+        }
+    }
+    Ok(scheduled)
+}
+
+/// Runs `apply`, an application of `transform`, logging the name of
+/// `transform` around it if `trace` is set.
+fn traced<T>(
+    transform: &dyn Transform,
+    trace: bool,
+    apply: impl FnOnce() -> Result<T, TransformError>,
+) -> Result<T, TransformError> {
+    if trace {
+        log::info!("starting transform {}", transform.name());
+    }
+    let result = apply()?;
+    if trace {
+        log::info!("finished transform {}", transform.name());
+    }
+    Ok(result)
 }
 
 /// Errors that can occur during a transformation.
@@ -79,6 +294,17 @@ pub enum TransformError {
     Eval(EvalError),
     /// An unstructured error.
     Internal(String),
+    /// The transform ran out of fuel before completing.
+    Exhausted,
+    /// The transform did not complete by its deadline.
+    Timeout,
+    /// The transform was given a relation, or arguments, it cannot apply to.
+    InvalidInput {
+        /// The name of the transform.
+        transform: &'static str,
+        /// What is wrong with the input, e.g. the operator and column at fault.
+        detail: String,
+    },
 }
 
 impl fmt::Display for TransformError {
@@ -86,6 +312,11 @@ impl fmt::Display for TransformError {
         match self {
             TransformError::Eval(e) => write!(f, "{}", e),
             TransformError::Internal(msg) => write!(f, "internal transform error: {}", msg),
+            TransformError::Exhausted => write!(f, "transform exhausted its fuel"),
+            TransformError::Timeout => write!(f, "transform exceeded its deadline"),
+            TransformError::InvalidInput { transform, detail } => {
+                write!(f, "invalid input to {}: {}", transform, detail)
+            }
         }
     }
 }
@@ -111,30 +342,64 @@ impl Transform for Fixpoint {
         relation: &mut RelationExpr,
         args: TransformArgs,
     ) -> Result<(), TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        mut args: TransformArgs,
+    ) -> Result<bool, TransformError> {
+        let mut fuel = args.fuel;
+        let mut settled = true;
         for _ in 0..self.limit {
+            consume_fuel(&mut fuel)?;
+            // The relation is copied once per round, rather than by each
+            // transform, and compared only if some transform reports that it
+            // may have changed it.
             let original = relation.clone();
+            let mut changed = false;
             for transform in self.transforms.iter() {
+                changed |= traced(&**transform, args.trace, || {
+                    transform.transform_changed(
+                        relation,
+                        TransformArgs {
+                            id_gen: args.id_gen,
+                            indexes: args.indexes,
+                            trace: args.trace,
+                            fuel,
+                            stats: args.stats.as_deref_mut(),
+                            source_requirements: args.source_requirements.as_deref_mut(),
+                            deadline: args.deadline,
+                        },
+                    )
+                })?;
+            }
+            // Transforms may undo each other's changes, or report changes
+            // they did not make, and so a round that reports changes may
+            // still leave the relation as it was.
+            if !changed || *relation == original {
+                return Ok(!settled);
+            }
+            settled = false;
+        }
+        consume_fuel(&mut fuel)?;
+        let original = relation.clone();
+        for transform in self.transforms.iter() {
+            traced(&**transform, args.trace, || {
                 transform.transform(
                     relation,
                     TransformArgs {
                         id_gen: args.id_gen,
                         indexes: args.indexes,
+                        trace: args.trace,
+                        fuel,
+                        stats: args.stats.as_deref_mut(),
+                        source_requirements: args.source_requirements.as_deref_mut(),
+                        deadline: args.deadline,
                     },
-                )?;
-            }
-            if *relation == original {
-                return Ok(());
-            }
-        }
-        let original = relation.clone();
-        for transform in self.transforms.iter() {
-            transform.transform(
-                relation,
-                TransformArgs {
-                    id_gen: args.id_gen,
-                    indexes: args.indexes,
-                },
-            )?;
+                )
+            })?;
         }
         Err(TransformError::Internal(format!(
             "fixpoint looped too many times {:#?} {}\n{}",
@@ -155,6 +420,10 @@ impl Transform for Fixpoint {
 pub struct Optimizer {
     /// The list of transforms to apply to an input relation.
     pub transforms: Vec<Box<dyn crate::Transform + Send>>,
+    /// Whether to log the name of each transform before and after it runs.
+    pub trace: bool,
+    /// The fuel given to each transform, if bounded.
+    pub fuel: Option<usize>,
 }
 
 impl Optimizer {
@@ -166,13 +435,16 @@ impl Optimizer {
     ) -> Result<(), TransformError> {
         let mut id_gen = Default::default();
         for transform in self.transforms.iter() {
-            transform.transform(
-                relation,
-                TransformArgs {
-                    id_gen: &mut id_gen,
-                    indexes,
-                },
-            )?;
+            traced(&**transform, self.trace, || {
+                transform.transform(
+                    relation,
+                    TransformArgs {
+                        trace: self.trace,
+                        fuel: self.fuel,
+                        ..TransformArgs::new(&mut id_gen, indexes)
+                    },
+                )
+            })?;
         }
         Ok(())
     }
@@ -222,7 +494,7 @@ impl Default for Optimizer {
                     Box::new(crate::projection_extraction::ProjectionExtraction),
                     Box::new(crate::projection_lifting::ProjectionLifting),
                     Box::new(crate::map_lifting::LiteralLifting),
-                    Box::new(crate::nonnull_requirements::NonNullRequirements),
+                    Box::new(crate::nonnull_requirements::NonNullRequirements::default()),
                     Box::new(crate::column_knowledge::ColumnKnowledge),
                     Box::new(crate::reduction_pushdown::ReductionPushdown),
                     Box::new(crate::redundant_join::RedundantJoin),
@@ -257,7 +529,11 @@ impl Default for Optimizer {
             Box::new(crate::fusion::project::Project),
             Box::new(crate::reduction::FoldConstants),
         ];
-        Self { transforms }
+        Self {
+            transforms,
+            trace: false,
+            fuel: None,
+        }
     }
 }
 
@@ -287,6 +563,265 @@ impl Optimizer {
             Box::new(crate::join_elision::JoinElision),
             Box::new(crate::empty_map::EmptyMap),
         ];
-        Self { transforms }
+        Self {
+            transforms,
+            trace: false,
+            fuel: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use expr::{BinaryFunc, GlobalId, Id, LocalId, RelationExpr, ScalarExpr, UnaryFunc};
+    use repr::{Datum, RelationType, ScalarType};
+
+    use super::{Fixpoint, Transform, TransformArgs, TransformError};
+
+    #[derive(Debug)]
+    struct Dummy;
+
+    impl Transform for Dummy {
+        fn transform(&self, _: &mut RelationExpr, _: TransformArgs) -> Result<(), TransformError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn default_reports_change() {
+        let mut relation = RelationExpr::constant(vec![], RelationType::empty());
+        let changed = Dummy
+            .transform_changed(
+                &mut relation,
+                TransformArgs::new(&mut Default::default(), &HashMap::new()),
+            )
+            .unwrap();
+        assert!(changed);
+    }
+
+    #[test]
+    fn fixpoint_ends_when_unchanged() {
+        // `Dummy` reports a change on each application, and so the fixpoint
+        // must find by comparison that the relation has settled.
+        let fixpoint = Fixpoint {
+            transforms: vec![Box::new(Dummy)],
+            limit: 1,
+        };
+        let mut relation = RelationExpr::constant(vec![], RelationType::empty());
+        fixpoint
+            .transform(
+                &mut relation,
+                TransformArgs::new(&mut Default::default(), &HashMap::new()),
+            )
+            .unwrap();
+    }
+
+    /// A transform that negates the relation, but reports no change.
+    #[derive(Debug)]
+    struct Unreported;
+
+    impl Transform for Unreported {
+        fn transform(
+            &self,
+            relation: &mut RelationExpr,
+            args: TransformArgs,
+        ) -> Result<(), TransformError> {
+            self.transform_changed(relation, args).map(|_| ())
+        }
+
+        fn transform_changed(
+            &self,
+            relation: &mut RelationExpr,
+            _: TransformArgs,
+        ) -> Result<bool, TransformError> {
+            *relation = relation.take_dangerous().negate();
+            Ok(false)
+        }
+    }
+
+    #[test]
+    fn fixpoint_trusts_unchanged() {
+        // A round in which no transform reports a change ends the fixpoint,
+        // without comparing the relation with its prior value, which would
+        // find it changed each round until the limit.
+        let fixpoint = Fixpoint {
+            transforms: vec![Box::new(Unreported)],
+            limit: 2,
+        };
+        let mut relation = RelationExpr::constant(vec![], RelationType::empty());
+        let changed = fixpoint
+            .transform_changed(
+                &mut relation,
+                TransformArgs::new(&mut Default::default(), &HashMap::new()),
+            )
+            .unwrap();
+        assert!(!changed);
+        assert_eq!(
+            relation,
+            RelationExpr::constant(vec![], RelationType::empty()).negate()
+        );
+
+        // A fixpoint reports a change only if some round changed the relation.
+        let fixpoint = Fixpoint {
+            transforms: vec![Box::new(Dummy)],
+            limit: 2,
+        };
+        let changed = fixpoint
+            .transform_changed(
+                &mut relation,
+                TransformArgs::new(&mut Default::default(), &HashMap::new()),
+            )
+            .unwrap();
+        assert!(!changed);
+    }
+
+    #[test]
+    fn unchanged_reports_are_true() {
+        // Each transform iterated by the default optimizer reports no change
+        // only when it leaves the relation as it was.
+        let transforms: Vec<Box<dyn Transform + Send>> = vec![
+            Box::new(crate::nonnullable::NonNullable),
+            Box::new(crate::reduction::FoldConstants),
+            Box::new(crate::predicate_pushdown::PredicatePushdown),
+            Box::new(crate::fusion::join::Join),
+            Box::new(crate::fusion::filter::Filter),
+            Box::new(crate::fusion::project::Project),
+            Box::new(crate::fusion::map::Map),
+            Box::new(crate::fusion::union::Union),
+            Box::new(crate::empty_map::EmptyMap),
+            Box::new(crate::join_elision::JoinElision),
+            Box::new(crate::reduce_elision::ReduceElision),
+            Box::new(crate::inline_let::InlineLet),
+            Box::new(crate::update_let::UpdateLet),
+            Box::new(crate::projection_extraction::ProjectionExtraction),
+            Box::new(crate::projection_lifting::ProjectionLifting),
+            Box::new(crate::map_lifting::LiteralLifting),
+            Box::new(crate::nonnull_requirements::NonNullRequirements::default()),
+            Box::new(crate::column_knowledge::ColumnKnowledge),
+            Box::new(crate::reduction_pushdown::ReductionPushdown),
+            Box::new(crate::redundant_join::RedundantJoin),
+            Box::new(crate::topk_elision::TopKElision),
+            Box::new(crate::reduction::NegatePredicate),
+            Box::new(crate::demand::Demand),
+            Box::new(crate::join_implementation::JoinImplementation),
+        ];
+        let typ = RelationType::new(vec![
+            ScalarType::Int64.nullable(true),
+            ScalarType::Int64.nullable(false),
+        ]);
+        let get = RelationExpr::Get {
+            id: Id::Global(GlobalId::User(1)),
+            typ: typ.clone(),
+        };
+        let local = RelationExpr::Get {
+            id: Id::Local(LocalId::new(0)),
+            typ: typ.clone(),
+        };
+        let one = ScalarExpr::literal_ok(Datum::Int64(1), ScalarType::Int64.nullable(false));
+        let not_null = |c| {
+            ScalarExpr::column(c)
+                .call_unary(UnaryFunc::IsNull)
+                .call_unary(UnaryFunc::Not)
+        };
+        let relations = vec![
+            get.clone().filter(vec![not_null(0)]).filter(vec![
+                ScalarExpr::column(1).call_binary(one.clone(), BinaryFunc::Eq)
+            ]),
+            get.clone()
+                .map(vec![ScalarExpr::column(0), one])
+                .project(vec![3, 2, 1]),
+            RelationExpr::join_scalars(
+                vec![get.clone(), get.clone()],
+                vec![vec![ScalarExpr::column(0), ScalarExpr::column(2)]],
+            )
+            .filter(vec![
+                ScalarExpr::column(1).call_binary(ScalarExpr::column(3), BinaryFunc::Eq)
+            ]),
+            RelationExpr::constant(vec![vec![Datum::Int64(1), Datum::Int64(2)]], typ)
+                .union(get.clone())
+                .negate(),
+            RelationExpr::Let {
+                id: LocalId::new(0),
+                value: Box::new(get.filter(vec![not_null(0)])),
+                body: Box::new(local.clone().union(local)),
+            },
+        ];
+        for mut relation in relations {
+            for _ in 0..3 {
+                for transform in transforms.iter() {
+                    let before = relation.clone();
+                    let changed = transform
+                        .transform_changed(
+                            &mut relation,
+                            TransformArgs::new(&mut Default::default(), &HashMap::new()),
+                        )
+                        .unwrap();
+                    if !changed {
+                        assert_eq!(relation, before, "{}", transform.name());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn default_name() {
+        assert_eq!(Dummy.name(), std::any::type_name::<Dummy>());
+        assert!(Dummy.name().ends_with("Dummy"));
+    }
+
+    /// A transform that does nothing, with the given name and requirements.
+    #[derive(Debug)]
+    struct Named(&'static str, &'static [&'static str]);
+
+    impl Transform for Named {
+        fn transform(&self, _: &mut RelationExpr, _: TransformArgs) -> Result<(), TransformError> {
+            Ok(())
+        }
+
+        fn name(&self) -> &'static str {
+            self.0
+        }
+
+        fn requires(&self) -> &'static [&'static str] {
+            self.1
+        }
+    }
+
+    #[test]
+    fn schedule_respects_requirements() {
+        let names = |transforms: Vec<Box<dyn Transform + Send>>| {
+            super::schedule(transforms).map(|transforms| {
+                transforms
+                    .iter()
+                    .map(|transform| transform.name())
+                    .collect::<Vec<_>>()
+            })
+        };
+        // `c` must follow `a`, and `b` must follow `c`; requirements on absent
+        // transforms are ignored, and the given order otherwise preserved.
+        assert_eq!(
+            names(vec![
+                Box::new(Named("b", &["c"])),
+                Box::new(Named("c", &["a", "missing"])),
+                Box::new(Named("d", &[])),
+                Box::new(Named("a", &[])),
+            ])
+            .unwrap(),
+            vec!["d", "a", "c", "b"]
+        );
+        assert!(names(vec![
+            Box::new(Named("a", &["b"])),
+            Box::new(Named("b", &["a"])),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn default_requirements() {
+        assert!(Dummy.requires().is_empty());
+        assert!(Dummy.invalidates().is_empty());
     }
 }
@@ -22,7 +22,7 @@
 
 use std::collections::HashMap;
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 use expr::{Id, JoinInputMapper, RelationExpr, ScalarExpr};
 use itertools::Itertools;
 
@@ -34,14 +34,23 @@ impl crate::Transform for LiteralLifting {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
-        let literals = self.action(relation, &mut HashMap::new());
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
+        let literals = self.action(relation, &mut HashMap::new(), &mut changed);
         if !literals.is_empty() {
             // Literals return up the root should be re-installed.
             *relation = relation.take_dangerous().map(literals);
         }
-        Ok(())
+        Ok(changed)
     }
 }
 
@@ -58,12 +67,16 @@ impl LiteralLifting {
     /// columns. This could be improved using permutations to move all of
     /// the literals to the final columns, and then rely on projection
     /// hoisting to allow the these literals to move up the AST.
+    ///
+    /// Sets `changed` if it changed `relation` other than by removing a `Map`
+    /// of the returned literals, which re-installing them would restore.
     // TODO(frank): Fix this.
     pub fn action(
         &self,
         relation: &mut RelationExpr,
         // Map from names to literals required for appending.
         gets: &mut HashMap<Id, Vec<ScalarExpr>>,
+        changed: &mut bool,
     ) -> Vec<ScalarExpr> {
         match relation {
             RelationExpr::Constant { rows, typ } => {
@@ -90,6 +103,7 @@ impl LiteralLifting {
                     literals.reverse();
 
                     if !literals.is_empty() {
+                        *changed = true;
                         // Tidy up the type information of `relation`.
                         for key in typ.keys.iter_mut() {
                             key.retain(|k| k < &data.len());
@@ -115,6 +129,7 @@ impl LiteralLifting {
                     // and not the same fields in its keys. It is ok to remove
                     // any columns from the keys, as them being literals meant
                     // that their distinctness was not what made anything a key.
+                    *changed = true;
                     for _ in 0..literals.len() {
                         typ.column_types.pop();
                     }
@@ -130,14 +145,17 @@ impl LiteralLifting {
             RelationExpr::Let { id, value, body } => {
                 // Any literals appended to the `value` should be used
                 // at corresponding `Get`s throughout the `body`.
-                let literals = self.action(value, gets);
+                let literals = self.action(value, gets, changed);
                 let id = Id::Local(*id);
                 if !literals.is_empty() {
+                    *changed = true;
                     let prior = gets.insert(id, literals);
                     assert!(!prior.is_some());
                 }
-                let result = self.action(body, gets);
+                let result = self.action(body, gets, changed);
                 gets.remove(&id);
+                // Literals lifted out of the body are lifted around the `Let`.
+                *changed |= !result.is_empty();
                 result
             }
             RelationExpr::Project { input, outputs } => {
@@ -145,7 +163,7 @@ impl LiteralLifting {
                 // Projections are the highest lifted operator and lifting
                 // literals around projections could cause us to fail to
                 // reach a fixed point under the transformations.
-                let mut literals = self.action(input, gets);
+                let mut literals = self.action(input, gets, changed);
                 if !literals.is_empty() {
                     let input_arity = input.arity();
                     if let Some(project_max) = outputs.iter().max() {
@@ -156,6 +174,7 @@ impl LiteralLifting {
                             && !literals.is_empty()
                         {
                             literals.pop();
+                            *changed = true;
                         }
                     }
                     // If the literals need to be re-interleaved,
@@ -171,7 +190,8 @@ impl LiteralLifting {
                 Vec::new()
             }
             RelationExpr::Map { input, scalars } => {
-                let mut literals = self.action(input, gets);
+                let mut literals = self.action(input, gets, changed);
+                *changed |= !literals.is_empty();
 
                 // Make the map properly formed again.
                 literals.extend(scalars.iter().cloned());
@@ -188,7 +208,12 @@ impl LiteralLifting {
                 result.reverse();
 
                 if scalars.is_empty() {
+                    // A `Map` of only the returned literals is restored by
+                    // re-installing them, but an empty `Map` is not.
+                    *changed |= result.is_empty();
                     *relation = input.take_dangerous();
+                } else {
+                    *changed |= !result.is_empty();
                 }
 
                 result
@@ -199,7 +224,7 @@ impl LiteralLifting {
                 exprs,
                 demand: _,
             } => {
-                let literals = self.action(input, gets);
+                let literals = self.action(input, gets, changed);
                 if !literals.is_empty() {
                     let input_arity = input.arity();
                     for expr in exprs.iter_mut() {
@@ -207,6 +232,7 @@ impl LiteralLifting {
                             if let ScalarExpr::Column(c) = e {
                                 if *c >= input_arity {
                                     *e = literals[*c - input_arity].clone();
+                                    *changed = true;
                                 }
                             }
                         });
@@ -219,8 +245,9 @@ impl LiteralLifting {
                 Vec::new()
             }
             RelationExpr::Filter { input, predicates } => {
-                let literals = self.action(input, gets);
+                let literals = self.action(input, gets, changed);
                 if !literals.is_empty() {
+                    *changed = true;
                     // We should be able to instantiate all uses of `literals`
                     // in predicates and then lift the `map` around the filter.
                     let input_arity = input.arity();
@@ -248,10 +275,11 @@ impl LiteralLifting {
                 // lift literals from each input
                 let mut input_literals = Vec::new();
                 for input in inputs.iter_mut() {
-                    input_literals.push(self.action(input, gets));
+                    input_literals.push(self.action(input, gets, changed));
                 }
 
                 if input_literals.iter().any(|l| !l.is_empty()) {
+                    *changed = true;
                     *demand = None;
                     *implementation = expr::JoinImplementation::Unimplemented;
 
@@ -316,8 +344,9 @@ impl LiteralLifting {
                 monotonic: _,
                 expected_group_size: _,
             } => {
-                let literals = self.action(input, gets);
+                let literals = self.action(input, gets, changed);
                 if !literals.is_empty() {
+                    *changed = true;
                     // Reduce absorbs maps, and we should inline literals.
                     let input_arity = input.arity();
                     // Inline literals into group key expressions.
@@ -366,6 +395,7 @@ impl LiteralLifting {
                     ));
                 }
                 result.reverse();
+                *changed |= !result.is_empty();
                 result
             }
             RelationExpr::TopK {
@@ -376,8 +406,9 @@ impl LiteralLifting {
                 offset: _,
                 monotonic: _,
             } => {
-                let literals = self.action(input, gets);
+                let literals = self.action(input, gets, changed);
                 if !literals.is_empty() {
+                    *changed = true;
                     // We should be able to lift literals out, as they affect neither
                     // grouping nor ordering. We should discard grouping and ordering
                     // that references the columns, though.
@@ -389,17 +420,21 @@ impl LiteralLifting {
             }
             RelationExpr::Negate { input } => {
                 // Literals can just be lifted out of negate.
-                self.action(input, gets)
+                let literals = self.action(input, gets, changed);
+                *changed |= !literals.is_empty();
+                literals
             }
             RelationExpr::Threshold { input } => {
                 // Literals can just be lifted out of threshold.
-                self.action(input, gets)
+                let literals = self.action(input, gets, changed);
+                *changed |= !literals.is_empty();
+                literals
             }
             RelationExpr::Union { base, inputs } => {
-                let mut base_literals = self.action(base, gets);
+                let mut base_literals = self.action(base, gets, changed);
                 let mut input_literals = inputs
                     .iter_mut()
-                    .map(|input| self.action(input, gets))
+                    .map(|input| self.action(input, gets, changed))
                     .collect::<Vec<Vec<ScalarExpr>>>();
 
                 // We need to find the longest common suffix between all the arms of the union.
@@ -420,6 +455,7 @@ impl LiteralLifting {
 
                 // Because we pushed stuff onto the vector like a stack, we need to reverse it now.
                 suffix.reverse();
+                *changed |= !suffix.is_empty();
 
                 // Any remaining literals for each expression must be appended to that expression,
                 // while the shared suffix is returned to continue traveling upwards.
@@ -438,8 +474,9 @@ impl LiteralLifting {
                 // as we disrupt the set of used arrangements. Though,
                 // we are probably most likely to use arranged `Get`
                 // operators rather than those decorated with maps.
-                let literals = self.action(input, gets);
+                let literals = self.action(input, gets, changed);
                 if !literals.is_empty() {
+                    *changed = true;
                     let input_arity = input.arity();
                     for key in keys.iter_mut() {
                         for expr in key.iter_mut() {
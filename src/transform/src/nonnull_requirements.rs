@@ -19,189 +19,3929 @@
 //! in support of outer-joins and subqueries, we can occasionally remove that
 //! branch when we observe that Null values would be subjected to predicates.
 //!
+//! For example, a left outer join is planned as the union of the inner join
+//! with the rows of the left input that have no match, padded with a literal
+//! null for each column of the right input. A predicate above the union that
+//! requires a column of the right input to be non-null reaches the padding
+//! `Map` through the union, and the padded branch is replaced by an empty
+//! constant, leaving only the inner join.
+//!
 //! This analysis relies on a careful understanding of `ScalarExpr` and the
 //! semantics of various functions, *some of which may be non-Null even with
 //! Null arguments*.
+use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+
+use crate::util::intersect_column_sets;
+use crate::{
+    ArrangementNullability, SourceRequirementReport, Transform, TransformArgs, TransformStats,
+};
+use expr::{
+    AggregateFunc, BinaryFunc, Id, JoinImplementation, JoinInputMapper, RelationExpr, ScalarExpr,
+    UnaryFunc,
+};
+use repr::RelationType;
 
-use crate::TransformArgs;
-use expr::{Id, JoinInputMapper, RelationExpr, ScalarExpr};
+thread_local! {
+    /// Requirements at each `Get`, reused across calls to `transform` to avoid
+    /// reallocating them.
+    ///
+    /// Each identifier maps to one set per reference to it, in the order
+    /// visited. References are never merged here; consumers intersect them,
+    /// as a column must be non-null only if each reference requires it.
+    static GETS: RefCell<HashMap<Id, Vec<HashSet<usize>>>> = RefCell::new(HashMap::new());
+}
 
 /// Push non-null requirements toward sources.
 #[derive(Debug)]
-pub struct NonNullRequirements;
+pub struct NonNullRequirements {
+    /// Whether to only record requirements, without pruning or zeroing out
+    /// anything to satisfy them.
+    analyze_only: bool,
+    /// Whether to remove rows of constant collections that fail the
+    /// requirements. Without it, expressions that can produce no rows that
+    /// satisfy the requirements are still zeroed out, but constants are left
+    /// as they are.
+    prune_constants: bool,
+    /// The depth of nested operators beyond which the analysis gives up,
+    /// rather than risk overflowing the stack.
+    recursion_limit: usize,
+    /// Whether to assume that any scalar function may be non-null on null
+    /// input, so that scalar expressions impose no requirements. Only the
+    /// nullability of types and literal nulls then drive pruning, which
+    /// guards against a function whose strictness is misclassified.
+    assume_all_non_strict: bool,
+}
+
+/// The default for `NonNullRequirements::recursion_limit`.
+pub const DEFAULT_RECURSION_LIMIT: usize = 2048;
+
+/// The number of expressions visited between checks of the deadline.
+const DEADLINE_CHECK_INTERVAL: usize = 64;
+
+/// Checks a deadline as expressions are visited, but only at intervals, as
+/// reading the clock at each would cost more than visiting many of them.
+struct Clock {
+    deadline: Option<Instant>,
+    visits: usize,
+}
+
+impl Clock {
+    fn new(deadline: Option<Instant>) -> Self {
+        Clock {
+            deadline,
+            visits: 0,
+        }
+    }
+
+    /// Counts a visit, failing if the deadline has passed as of the first
+    /// visit or of every `DEADLINE_CHECK_INTERVAL`th after it.
+    fn tick(&mut self) -> Result<(), crate::TransformError> {
+        if self.deadline.is_some() {
+            if self.visits % DEADLINE_CHECK_INTERVAL == 0 {
+                crate::check_deadline(self.deadline)?;
+            }
+            self.visits += 1;
+        }
+        Ok(())
+    }
+}
+
+/// The state of a pass of `NonNullRequirements` over a plan, as its operators
+/// are visited.
+struct Visit<'a> {
+    /// Requirements at each `Get` visited so far, as for `GETS`.
+    gets: HashMap<Id, Vec<HashSet<usize>>>,
+    /// The fuel left for visiting expressions, if limited.
+    fuel: &'a mut Option<usize>,
+    clock: Clock,
+    stats: &'a mut TransformStats,
+    /// Whether to log expressions as they are zeroed out.
+    trace: bool,
+    /// The path of the expression being visited, as for
+    /// `NonNullRequirements::action_at_path`, whose length is its depth.
+    path: Vec<usize>,
+    /// The paths of the expressions zeroed out so far, and of the constants
+    /// whose every row was pruned, in the order visited.
+    eliminated: Vec<Vec<usize>>,
+    /// The analysis recorded for each subexpression, if the pass is
+    /// incremental, to be reused and brought up to date as it goes.
+    incremental: Option<&'a mut NonNullAnalysis>,
+    /// The provenance of the requirements at each `Get` visited so far, in
+    /// the order of `gets`, if tracked.
+    provenances: HashMap<Id, Vec<Provenance>>,
+}
+
+impl<'a> Visit<'a> {
+    /// A pass that starts from the requirements `gets`, without a deadline.
+    fn new(
+        gets: HashMap<Id, Vec<HashSet<usize>>>,
+        fuel: &'a mut Option<usize>,
+        stats: &'a mut TransformStats,
+    ) -> Self {
+        Visit {
+            gets,
+            fuel,
+            clock: Clock::new(None),
+            stats,
+            trace: false,
+            path: Vec::new(),
+            eliminated: Vec::new(),
+            incremental: None,
+            provenances: HashMap::new(),
+        }
+    }
+}
+
+/// The origin of a requirement, as `NonNullRequirements::action_returning`
+/// tracks them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Origin {
+    /// The requirement on a column at the root.
+    Root(usize),
+    /// The plan itself, e.g. a predicate, whatever the requirements at the
+    /// root.
+    Plan,
+}
+
+/// The origins of the requirement on each required column, if tracked.
+///
+/// Untracked provenance records nothing, and costs nothing to maintain.
+/// Tracked provenance has an entry for each required column.
+#[derive(Clone, Debug, Default)]
+struct Provenance(Option<HashMap<usize, HashSet<Origin>>>);
+
+impl Provenance {
+    /// Tracked provenance of the requirements `columns` at the root, each of
+    /// which is its own origin.
+    fn roots(columns: &HashSet<usize>) -> Self {
+        Provenance(Some(
+            columns
+                .iter()
+                .map(|c| (*c, Some(Origin::Root(*c)).into_iter().collect()))
+                .collect(),
+        ))
+    }
+
+    fn is_tracked(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Provenance with the same tracking, but of no requirements.
+    fn emptied(&self) -> Self {
+        Provenance(self.0.as_ref().map(|_| HashMap::new()))
+    }
+
+    /// The origins of the requirement on `column`, which are none if it is
+    /// not required or not tracked.
+    fn origins(&self, column: usize) -> HashSet<Origin> {
+        self.0
+            .as_ref()
+            .and_then(|origins| origins.get(&column))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Adds `origins` to those of each of `columns`.
+    fn add(&mut self, columns: impl IntoIterator<Item = usize>, origins: &HashSet<Origin>) {
+        if let Some(map) = &mut self.0 {
+            for column in columns {
+                map.entry(column).or_default().extend(origins);
+            }
+        }
+    }
+
+    /// Adds to `columns` the requirements that `derive` adds to a set, each
+    /// with the origins `origins`.
+    fn extend(
+        &mut self,
+        columns: &mut HashSet<usize>,
+        origins: &HashSet<Origin>,
+        derive: impl FnOnce(&mut HashSet<usize>),
+    ) {
+        if self.is_tracked() {
+            let mut derived = HashSet::new();
+            derive(&mut derived);
+            self.add(derived.iter().copied(), origins);
+            columns.extend(derived);
+        } else {
+            derive(columns);
+        }
+    }
+
+    /// Adds to `columns` the requirements that `derive` adds to a set, as
+    /// requirements of the plan itself.
+    fn extend_from_plan(
+        &mut self,
+        columns: &mut HashSet<usize>,
+        derive: impl FnOnce(&mut HashSet<usize>),
+    ) {
+        let origins = if self.is_tracked() {
+            Some(Origin::Plan).into_iter().collect()
+        } else {
+            HashSet::new()
+        };
+        self.extend(columns, &origins, derive);
+    }
+
+    /// Discards the origins of columns other than `columns`.
+    fn retain(&mut self, columns: &HashSet<usize>) {
+        if let Some(map) = &mut self.0 {
+            map.retain(|column, _| columns.contains(column));
+        }
+    }
+
+    /// Renumbers each column `c` as `renumber(c)`, merging the origins of
+    /// columns renumbered alike.
+    fn renumber(&self, renumber: impl Fn(usize) -> Option<usize>) -> Self {
+        let mut provenance = self.emptied();
+        if let Some(map) = &self.0 {
+            for (column, origins) in map {
+                provenance.add(renumber(*column), origins);
+            }
+        }
+        provenance
+    }
+
+    /// The provenance of the requirements `columns` that each of several
+    /// references to a binding shares, with the provenance of each reference
+    /// in `references`.
+    ///
+    /// A column that the plan requires at each reference is required by the
+    /// plan, and otherwise by each origin that requires it at some reference.
+    fn intersect(&self, references: &[Provenance], columns: &HashSet<usize>) -> Self {
+        let mut provenance = self.emptied();
+        if provenance.is_tracked() {
+            for column in columns {
+                let mut origins = HashSet::new();
+                let mut from_plan = true;
+                for reference in references {
+                    let reference = reference.origins(*column);
+                    from_plan &= reference.contains(&Origin::Plan);
+                    origins.extend(reference);
+                }
+                if !from_plan {
+                    origins.remove(&Origin::Plan);
+                }
+                provenance.add(Some(*column), &origins);
+            }
+        }
+        provenance
+    }
+
+    /// The root columns that each require some column, which the plan does
+    /// not require anyway.
+    fn escaped(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0
+            .iter()
+            .flat_map(|map| map.values())
+            .filter(|origins| !origins.contains(&Origin::Plan))
+            .flatten()
+            .filter_map(|origin| match origin {
+                Origin::Root(column) => Some(*column),
+                Origin::Plan => None,
+            })
+    }
+}
+
+impl Default for NonNullRequirements {
+    fn default() -> Self {
+        NonNullRequirements {
+            analyze_only: false,
+            prune_constants: true,
+            recursion_limit: DEFAULT_RECURSION_LIMIT,
+            assume_all_non_strict: false,
+        }
+    }
+}
 
 impl crate::Transform for NonNullRequirements {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
-        self.action(relation, HashSet::new(), &mut HashMap::new());
-        Ok(())
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        args: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        self.transform_seeded(relation, HashSet::new(), args)
+    }
+
+    fn name(&self) -> &'static str {
+        "NonNullRequirements"
+    }
+
+    fn invalidates(&self) -> &'static [&'static str] {
+        // Pruning rows and zeroing expressions may make columns non-null that
+        // were not before.
+        &["nullability"]
+    }
+}
+
+/// The outcome of `NonNullRequirements::analyze`, from which each view of
+/// the requirements of a plan is derived.
+#[derive(Clone, Debug, Default)]
+pub struct NonNullAnalysis {
+    /// The requirements at each `Get`, keyed by the identifier it references.
+    ///
+    /// Bound identifiers are not included, as their requirements are pushed
+    /// at the values to which they are bound. Each identifier has one set of
+    /// requirements for each `Get` of it, in the order visited, whether the
+    /// `Get`s are in the same subtree or not.
+    pub gets: HashMap<Id, Vec<HashSet<usize>>>,
+    /// The paths of the subexpressions that would be zeroed out, or of the
+    /// constants whose every row would be pruned, in the order visited.
+    ///
+    /// Paths are as for `NonNullRequirements::action_at_path`.
+    pub eliminable_paths: Vec<Vec<usize>>,
+    /// What the analysis recorded for each subexpression, by its path, for
+    /// `NonNullRequirements::reanalyze` to reuse.
+    ///
+    /// Only `NonNullRequirements::analyze_incremental` and `reanalyze` record
+    /// these, and an analysis without them reuses nothing when reanalyzed.
+    recorded: HashMap<Vec<usize>, Recorded>,
+    /// The number of subexpressions whose records the analysis reused rather
+    /// than derived anew.
+    reused: usize,
+}
+
+impl NonNullAnalysis {
+    /// The columns of each global source that must be non-null, which are
+    /// those that each `Get` of the source requires.
+    pub fn requirements(&self) -> HashMap<Id, HashSet<usize>> {
+        self.gets
+            .iter()
+            .filter(|(id, _)| matches!(id, Id::Global(_)))
+            .map(|(id, needs)| (*id, intersect_column_sets(needs.clone())))
+            .collect()
+    }
+
+    /// The number of branches that would be eliminated, either by zeroing
+    /// them out or by pruning each row of a constant.
+    ///
+    /// Planners may use this to weigh the cost of introducing null padding
+    /// that would later be eliminated.
+    pub fn eliminable_branches(&self) -> usize {
+        self.eliminable_paths.len()
     }
 }
 
+/// What an analysis recorded for a subexpression.
+#[derive(Clone, Debug, Default)]
+struct Recorded {
+    /// The requirements the subexpression was visited with.
+    columns: HashSet<usize>,
+    /// The requirements at each `Get` within the subexpression.
+    gets: HashMap<Id, Vec<HashSet<usize>>>,
+    /// The paths of the subexpressions within it that would be eliminated.
+    eliminable_paths: Vec<Vec<usize>>,
+}
+
 impl NonNullRequirements {
+    /// A variant that records requirements without acting on them, leaving
+    /// relations unchanged.
+    pub fn analyze_only() -> Self {
+        NonNullRequirements {
+            analyze_only: true,
+            ..Default::default()
+        }
+    }
+
+    /// Sets whether to remove rows of constant collections that fail the
+    /// requirements, which it does by default.
+    pub fn with_prune_constants(mut self, prune_constants: bool) -> Self {
+        self.prune_constants = prune_constants;
+        self
+    }
+
+    /// Sets the depth of nested operators beyond which the analysis returns
+    /// an error.
+    pub fn with_recursion_limit(mut self, recursion_limit: usize) -> Self {
+        self.recursion_limit = recursion_limit;
+        self
+    }
+
+    /// Sets whether to assume that any scalar function may be non-null on
+    /// null input, which it does not by default.
+    pub fn with_assume_all_non_strict(mut self, assume_all_non_strict: bool) -> Self {
+        self.assume_all_non_strict = assume_all_non_strict;
+        self
+    }
+
+    /// Push non-null requirements toward sources, starting from the
+    /// requirements `required` on the columns of `relation`.
+    ///
+    /// Callers that know more of the context of `relation`, e.g. that it is
+    /// to be written to an index on non-null columns, may prune more rows this
+    /// way than `transform` does.
+    pub fn transform_with_required(
+        &self,
+        relation: &mut RelationExpr,
+        required: HashSet<usize>,
+        args: TransformArgs,
+    ) -> Result<(), crate::TransformError> {
+        let arity = relation.arity();
+        if let Some(column) = required.iter().filter(|c| **c >= arity).min() {
+            return Err(crate::TransformError::InvalidInput {
+                transform: self.name(),
+                detail: format!(
+                    "non-null requirement on column {} of a relation of arity {}",
+                    column, arity
+                ),
+            });
+        }
+        self.transform_seeded(relation, required, args).map(|_| ())
+    }
+
+    /// Implements `transform_changed`, starting from the requirements
+    /// `columns` at the root.
+    ///
+    /// The requirements on each global source are recorded in
+    /// `args.source_requirements`, if it is set and the analysis succeeds,
+    /// along with the nullability of the keys of each arrangement.
+    fn transform_seeded(
+        &self,
+        relation: &mut RelationExpr,
+        columns: HashSet<usize>,
+        args: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut fuel = args.fuel;
+        let mut unrecorded = TransformStats::default();
+        let stats = match args.stats {
+            Some(stats) => stats,
+            None => &mut unrecorded,
+        };
+        let report = args.source_requirements;
+        GETS.with(|gets| {
+            // As in `action_with_scratch`, but tracing if requested.
+            let gets = &mut gets.borrow_mut();
+            gets.clear();
+            // The scratch space is moved into the pass, and back out of it, to
+            // reuse its allocation.
+            let mut cx = Visit {
+                clock: Clock::new(args.deadline),
+                trace: args.trace,
+                ..Visit::new(std::mem::take(&mut **gets), &mut fuel, stats)
+            };
+            let mut result = self.visit_root(relation, columns, Provenance::default(), &mut cx);
+            **gets = cx.gets;
+            if let (true, Some(report)) = (result.is_ok(), report) {
+                for (id, needs) in gets.drain() {
+                    if let Id::Global(id) = id {
+                        report.record(id, intersect_column_sets(needs));
+                    }
+                }
+                // The types are derived anew, as the columns of an arranged
+                // input may have been proven non-null by pruning it.
+                match TypeTree::derive(relation, true, self.recursion_limit) {
+                    Ok(known) => record_arrangements(relation, &known, report),
+                    Err(error) => result = Err(error),
+                }
+            }
+            gets.clear();
+            result
+        })
+    }
+
+    /// Determines the requirements of `relation`, and the branches that the
+    /// transform would eliminate to satisfy them, without changing `relation`.
+    ///
+    /// The requirements are those that the transform would push, and so
+    /// references within eliminable branches contribute none. Whether the
+    /// transform only records requirements does not affect the analysis.
+    pub fn analyze(
+        &self,
+        relation: &RelationExpr,
+    ) -> Result<NonNullAnalysis, crate::TransformError> {
+        self.analyze_recording(relation, NonNullAnalysis::default(), false)
+    }
+
+    /// Determines the analysis of `relation`, as `analyze` does, recording
+    /// what it determines of each subexpression for `reanalyze` to reuse.
+    ///
+    /// The records hold the requirements within each subexpression, and so
+    /// take space in the depth of `relation` times its references.
+    pub fn analyze_incremental(
+        &self,
+        relation: &RelationExpr,
+    ) -> Result<NonNullAnalysis, crate::TransformError> {
+        self.analyze_recording(relation, NonNullAnalysis::default(), true)
+    }
+
+    /// Determines the analysis of `relation` anew once the subexpression at
+    /// `path` has changed, reusing what `previous` recorded for the rest of
+    /// `relation`.
+    ///
+    /// The changed subexpression and those that contain it are analyzed anew,
+    /// as is the body of each `Let` whose value contains it, as the types of
+    /// the `Get`s of the value may have changed. Any other subexpression is
+    /// unchanged, and its records are reused if it is visited with the same
+    /// requirements as before; a change may alter those of its siblings, e.g.
+    /// through the equivalences of a join. The empty path reuses nothing.
+    ///
+    /// The analysis returned records what it determines, as that of
+    /// `analyze_incremental` does.
+    pub fn reanalyze(
+        &self,
+        relation: &RelationExpr,
+        path: &[usize],
+        previous: NonNullAnalysis,
+    ) -> Result<NonNullAnalysis, crate::TransformError> {
+        let mut stale = vec![path.to_vec()];
+        let mut node = relation;
+        for (depth, index) in path.iter().enumerate() {
+            if let RelationExpr::Let { .. } = node {
+                if *index == 0 {
+                    stale.push(path[..depth].iter().copied().chain(Some(1)).collect());
+                }
+            }
+            let mut children = Vec::new();
+            node.visit1(|child| children.push(child));
+            match children.get(*index) {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+        let mut recorded = previous.recorded;
+        recorded.retain(|other, _| {
+            stale
+                .iter()
+                .all(|stale| !other.starts_with(stale) && !stale.starts_with(other))
+        });
+        let analysis = NonNullAnalysis {
+            recorded,
+            ..NonNullAnalysis::default()
+        };
+        self.analyze_recording(relation, analysis, true)
+    }
+
+    /// Determines the analysis of `relation`, reusing the records of
+    /// `analysis` and bringing them up to date if `record` is set.
+    fn analyze_recording(
+        &self,
+        relation: &RelationExpr,
+        mut analysis: NonNullAnalysis,
+        record: bool,
+    ) -> Result<NonNullAnalysis, crate::TransformError> {
+        let transform = NonNullRequirements {
+            analyze_only: false,
+            prune_constants: self.prune_constants,
+            recursion_limit: self.recursion_limit,
+            assume_all_non_strict: self.assume_all_non_strict,
+        };
+        // The transform acts on a copy of `relation`, whose eliminated
+        // branches are then those it reports.
+        let mut fuel = None;
+        let mut stats = TransformStats::default();
+        let mut cx = Visit {
+            incremental: if record { Some(&mut analysis) } else { None },
+            ..Visit::new(HashMap::new(), &mut fuel, &mut stats)
+        };
+        transform.visit_root(
+            &mut relation.clone(),
+            HashSet::new(),
+            Provenance::default(),
+            &mut cx,
+        )?;
+        let (gets, eliminated) = (cx.gets, cx.eliminated);
+        analysis.gets = gets;
+        analysis.eliminable_paths = eliminated;
+        Ok(analysis)
+    }
+
+    /// Push non-null requirements toward sources, using `gets` as scratch
+    /// space.
+    ///
+    /// Unlike `action`, this starts from and leaves behind an empty `gets`, so
+    /// that requirements from one relation cannot leak into the next.
+    pub fn action_with_scratch(
+        &self,
+        relation: &mut RelationExpr,
+        columns: HashSet<usize>,
+        gets: &mut HashMap<Id, Vec<HashSet<usize>>>,
+        fuel: &mut Option<usize>,
+        stats: &mut TransformStats,
+    ) -> Result<bool, crate::TransformError> {
+        gets.clear();
+        let result = self.action(relation, columns, gets, fuel, stats);
+        gets.clear();
+        result
+    }
+
+    /// Push non-null requirements toward sources, from the requirements
+    /// `columns` on the subexpression of `relation` at `path`, leaving the
+    /// rest of `relation` unchanged.
+    ///
+    /// Each element of `path` indexes the children of an expression, in the
+    /// order `RelationExpr::visit1_mut` visits them, e.g. the value of a
+    /// `Let` before its body. This isolates the effect of the analysis on a
+    /// single subplan. Requirements on identifiers bound outside the
+    /// subexpression are discarded.
+    pub fn action_at_path(
+        &self,
+        relation: &mut RelationExpr,
+        path: &[usize],
+        columns: HashSet<usize>,
+    ) -> Result<bool, crate::TransformError> {
+        let mut node = relation;
+        for (depth, index) in path.iter().enumerate() {
+            let name = operator_name(node);
+            let mut children = Vec::new();
+            let parent = node;
+            parent.visit1_mut(|child| children.push(child));
+            let count = children.len();
+            node = match children.into_iter().nth(*index) {
+                Some(child) => child,
+                None => {
+                    return Err(crate::TransformError::InvalidInput {
+                        transform: self.name(),
+                        detail: format!(
+                            "path {:?} addresses child {} of a {} with {} children at depth {}",
+                            path, index, name, count, depth
+                        ),
+                    })
+                }
+            };
+        }
+        self.action(
+            node,
+            columns,
+            &mut HashMap::new(),
+            &mut None,
+            &mut TransformStats::default(),
+        )
+    }
+
     /// Push non-null requirements toward sources.
+    ///
+    /// Each visited expression consumes one unit of `fuel`, if it is set.
+    /// Expressions nested more deeply than the recursion limit are not
+    /// visited, and result in an error instead. Returns whether any rows were
+    /// pruned or expressions zeroed out, and counts each of these in `stats`.
     pub fn action(
         &self,
         relation: &mut RelationExpr,
-        mut columns: HashSet<usize>,
+        columns: HashSet<usize>,
         gets: &mut HashMap<Id, Vec<HashSet<usize>>>,
-    ) {
+        fuel: &mut Option<usize>,
+        stats: &mut TransformStats,
+    ) -> Result<bool, crate::TransformError> {
+        let mut cx = Visit::new(std::mem::take(gets), fuel, stats);
+        let result = self.visit_root(relation, columns, Provenance::default(), &mut cx);
+        *gets = cx.gets;
+        result
+    }
+
+    /// Derives the column types of `relation` and each of its subexpressions,
+    /// with the columns proven non-null typed non-nullable unless all scalar
+    /// functions are assumed non-strict, as proofs from filters rely on their
+    /// strictness.
+    fn type_tree(&self, relation: &RelationExpr) -> Result<TypeTree, crate::TransformError> {
+        TypeTree::derive(relation, !self.assume_all_non_strict, self.recursion_limit)
+    }
+
+    /// Push non-null requirements toward sources, as `action` does, returning
+    /// those of `columns` whose requirements reach a `Get`.
+    ///
+    /// The requirement on a column reaches a `Get` if, in this action, it
+    /// requires a column of the referenced collection that the plan does not
+    /// require anyway, e.g. by a predicate. Others are absorbed along the way,
+    /// e.g. by a `Map` whose literal null they zero out, or by a `count`,
+    /// which is never null. The origins of each requirement are tracked as
+    /// requirements are pushed, in the same pass that acts on `relation`.
+    pub fn action_returning(
+        &self,
+        relation: &mut RelationExpr,
+        columns: HashSet<usize>,
+        gets: &mut HashMap<Id, Vec<HashSet<usize>>>,
+    ) -> Result<HashSet<usize>, crate::TransformError> {
+        let mut fuel = None;
+        let mut stats = TransformStats::default();
+        let mut cx = Visit::new(std::mem::take(gets), &mut fuel, &mut stats);
+        let provenance = Provenance::roots(&columns);
+        let result = self.visit_root(relation, columns, provenance, &mut cx);
+        *gets = cx.gets;
+        result?;
+        // Those of bound identifiers were pushed at the values bound.
+        Ok(cx
+            .provenances
+            .values()
+            .flatten()
+            .flat_map(Provenance::escaped)
+            .collect())
+    }
+
+    /// Adds to `columns` those that must be non-null for `expr` to be
+    /// non-null, unless all scalar functions are assumed non-strict.
+    fn scalar_non_null_requirements(&self, expr: &ScalarExpr, columns: &mut HashSet<usize>) {
+        if !self.assume_all_non_strict {
+            expr.non_null_requirements(columns);
+        }
+    }
+
+    /// Push non-null requirements toward sources, from the root of
+    /// `relation`, deriving its column types first.
+    fn visit_root(
+        &self,
+        relation: &mut RelationExpr,
+        columns: HashSet<usize>,
+        provenance: Provenance,
+        cx: &mut Visit,
+    ) -> Result<bool, crate::TransformError> {
+        let known = self.type_tree(relation)?;
+        self.visit(relation, columns, provenance, &known, cx)
+    }
+
+    /// Push non-null requirements toward sources, from the child at `index`
+    /// of the expression at `cx.path`.
+    fn visit_child(
+        &self,
+        index: usize,
+        relation: &mut RelationExpr,
+        columns: HashSet<usize>,
+        provenance: Provenance,
+        known: &TypeTree,
+        cx: &mut Visit,
+    ) -> Result<bool, crate::TransformError> {
+        cx.path.push(index);
+        let result = self.visit(relation, columns, provenance, known, cx);
+        cx.path.pop();
+        result
+    }
+
+    /// Push non-null requirements toward sources, from the expression at
+    /// `cx.path`, reusing the requirements recorded for it if the pass is
+    /// incremental and it is visited with the same requirements as before.
+    ///
+    /// `known` holds the column types of `relation` and its subexpressions,
+    /// as derived before any of them is visited, and `provenance` the origins
+    /// of `columns`, if tracked. Incremental passes do not track them.
+    fn visit(
+        &self,
+        relation: &mut RelationExpr,
+        columns: HashSet<usize>,
+        provenance: Provenance,
+        known: &TypeTree,
+        cx: &mut Visit,
+    ) -> Result<bool, crate::TransformError> {
+        if cx.incremental.is_none() {
+            return self.visit_operator(relation, columns, provenance, known, cx);
+        }
+        if let Some(incremental) = &mut cx.incremental {
+            if let Some(recorded) = incremental.recorded.get(&cx.path) {
+                if recorded.columns == columns {
+                    incremental.reused += 1;
+                    extend_gets(&mut cx.gets, recorded.gets.clone());
+                    cx.eliminated
+                        .extend(recorded.eliminable_paths.iter().cloned());
+                    return Ok(false);
+                }
+            }
+        }
+        // What the expression records is recorded apart from that of the rest
+        // of the plan, and then added to it.
+        let outer_gets = std::mem::take(&mut cx.gets);
+        let outer_eliminated = std::mem::take(&mut cx.eliminated);
+        let result = self.visit_operator(relation, columns.clone(), provenance, known, cx);
+        let gets = std::mem::replace(&mut cx.gets, outer_gets);
+        let eliminated = std::mem::replace(&mut cx.eliminated, outer_eliminated);
+        let changed = result?;
+        if let Some(incremental) = &mut cx.incremental {
+            let recorded = Recorded {
+                columns,
+                gets: gets.clone(),
+                eliminable_paths: eliminated.clone(),
+            };
+            incremental.recorded.insert(cx.path.clone(), recorded);
+        }
+        extend_gets(&mut cx.gets, gets);
+        cx.eliminated.extend(eliminated);
+        Ok(changed)
+    }
+
+    /// Push non-null requirements toward sources, from the expression at
+    /// `cx.path`, logging any expressions zeroed out if `cx.trace` is set.
+    fn visit_operator(
+        &self,
+        relation: &mut RelationExpr,
+        mut columns: HashSet<usize>,
+        mut provenance: Provenance,
+        known: &TypeTree,
+        cx: &mut Visit,
+    ) -> Result<bool, crate::TransformError> {
+        if cx.path.len() > self.recursion_limit {
+            return Err(crate::TransformError::Internal(
+                "recursion limit exceeded".into(),
+            ));
+        }
+        // Each arm passes its children the requirements it derives for them,
+        // and so checking requirements on entry checks each arm's derivation,
+        // e.g. that of a scalar that reports columns beyond its input. The
+        // arity is that of `known`, which spares determining it anew.
+        let arity = known.arity();
+        // The least such column is reported, so that the error does not
+        // depend on the order in which `columns` iterates.
+        if let Some(column) = columns.iter().filter(|c| **c >= arity).min() {
+            return Err(crate::TransformError::InvalidInput {
+                transform: self.name(),
+                detail: format!(
+                    "non-null requirement on column {} of {} of arity {}",
+                    column,
+                    operator_name(relation),
+                    arity
+                ),
+            });
+        }
+        crate::consume_fuel(cx.fuel)?;
+        cx.clock.tick()?;
+        let mut changed = false;
         match relation {
-            RelationExpr::Constant { rows, .. } => rows.retain(|(row, _)| {
-                let datums = row.unpack();
-                columns.iter().all(|c| datums[*c] != repr::Datum::Null)
-            }),
+            // Without requirements there are no rows to prune, which saves
+            // unpacking each of them.
+            RelationExpr::Constant { .. }
+                if self.analyze_only || !self.prune_constants || columns.is_empty() => {}
+            RelationExpr::Constant { rows, .. } => {
+                // Datums are decoded in order, and only as far as the last
+                // required column, rather than unpacking each row.
+                let end = columns.iter().max().map_or(0, |c| c + 1);
+                let len = rows.len();
+                rows.retain(|(row, _)| {
+                    !row.iter()
+                        .take(end)
+                        .enumerate()
+                        .any(|(c, datum)| datum.is_null() && columns.contains(&c))
+                });
+                cx.stats.constant_rows_pruned += len - rows.len();
+                if len > 0 && rows.is_empty() {
+                    cx.stats.constants_emptied += 1;
+                    cx.eliminated.push(cx.path.clone());
+                }
+                changed = rows.len() != len;
+            }
             RelationExpr::Get { id, .. } => {
-                gets.entry(*id).or_insert_with(Vec::new).push(columns);
+                // Local and global ids alike record each reference apart, to
+                // be intersected by whoever acts on them.
+                cx.gets.entry(*id).or_insert_with(Vec::new).push(columns);
+                if provenance.is_tracked() {
+                    cx.provenances
+                        .entry(*id)
+                        .or_insert_with(Vec::new)
+                        .push(provenance);
+                }
             }
             RelationExpr::Let { id, value, body } => {
                 // Let harvests any non-null requirements from its body,
                 // and acts on the intersection of the requirements for
                 // each corresponding Get, pushing them at its value.
+                //
+                // A `Let` may shadow an enclosing binding of the same `id`.
+                // The requirements recorded for the enclosing binding so far
+                // are set aside while the body is visited, and restored
+                // exactly before the value is visited, as references to `id`
+                // in the value are to the enclosing binding.
                 let id = Id::Local(*id);
-                let prior = gets.insert(id, Vec::new());
-                self.action(body, columns, gets);
-                let mut needs = gets.remove(&id).unwrap();
+                let prior = cx.gets.insert(id, Vec::new());
+                let tracked = provenance.emptied();
+                let prior_provenances = if tracked.is_tracked() {
+                    cx.provenances.insert(id, Vec::new())
+                } else {
+                    None
+                };
+                let (value_known, body_known) = (&known.inputs[0], &known.inputs[1]);
+                changed |= self.visit_child(1, body, columns, provenance, body_known, cx)?;
+                // A body with no references to `id` records no requirements,
+                // and its `value` is then left as is.
+                let needs = cx.gets.remove(&id).unwrap_or_else(Vec::new);
                 if let Some(prior) = prior {
-                    gets.insert(id, prior);
+                    cx.gets.insert(id, prior);
                 }
-                if let Some(mut need) = needs.pop() {
-                    while let Some(x) = needs.pop() {
-                        need.retain(|col| x.contains(col))
+                let provenances = if tracked.is_tracked() {
+                    let provenances = cx.provenances.remove(&id).unwrap_or_else(Vec::new);
+                    if let Some(prior) = prior_provenances {
+                        cx.provenances.insert(id, prior);
                     }
-                    self.action(value, need, gets);
+                    provenances
+                } else {
+                    Vec::new()
+                };
+                if !needs.is_empty() {
+                    let need = intersect_column_sets(needs);
+                    let provenance = tracked.intersect(&provenances, &need);
+                    changed |= self.visit_child(0, value, need, provenance, value_known, cx)?;
                 }
             }
             RelationExpr::Project { input, outputs } => {
-                self.action(
-                    input,
-                    columns.into_iter().map(|c| outputs[c]).collect(),
-                    gets,
-                );
+                let provenance = provenance.renumber(|c| outputs.get(c).copied());
+                let columns = columns
+                    .into_iter()
+                    .map(|c| {
+                        outputs
+                            .get(c)
+                            .copied()
+                            .ok_or_else(|| crate::TransformError::InvalidInput {
+                                transform: self.name(),
+                                detail: format!(
+                                    "non-null requirement on column {} of a projection of arity {}",
+                                    c,
+                                    outputs.len()
+                                ),
+                            })
+                    })
+                    .collect::<Result<HashSet<_>, _>>()?;
+                changed |= self.visit_child(0, input, columns, provenance, &known.inputs[0], cx)?;
+            }
+            // Without requirements on its columns, a map imposes none on its
+            // input, and neither its arity nor its scalars need inspecting.
+            RelationExpr::Map { input, .. } if columns.is_empty() => {
+                changed |= self.visit_child(0, input, columns, provenance, &known.inputs[0], cx)?;
             }
             RelationExpr::Map { input, scalars } => {
-                let arity = input.arity();
-                if columns
+                let arity = known.inputs[0].arity();
+                // The least such column is reported, so that neither error
+                // nor trace depends on the order in which `columns` iterates.
+                let width = arity + scalars.len();
+                if let Some(column) = columns.iter().filter(|c| **c >= width).min() {
+                    return Err(crate::TransformError::InvalidInput {
+                        transform: self.name(),
+                        detail: format!(
+                            "non-null requirement on column {} of a map of arity {}",
+                            column, width
+                        ),
+                    });
+                }
+                let null_column = columns
                     .iter()
-                    .any(|c| *c >= arity && scalars[*c - arity].is_literal_null())
-                {
+                    .filter(|c| **c >= arity)
+                    .filter_map(|c| {
+                        scalars
+                            .get(c - arity)
+                            .filter(|scalar| scalar.is_literal_null())
+                            .map(|scalar| (*c, scalar))
+                    })
+                    .min_by_key(|(c, _)| *c);
+                if let Some((column, scalar)) = null_column {
                     // A null value was introduced in a marked column;
                     // the entire expression can be zerod out.
-                    relation.take_safely();
+                    if !self.analyze_only {
+                        if cx.trace {
+                            log::warn!("{}", zeroed_map_message(column, arity, scalar));
+                        }
+                        relation.take_safely();
+                        cx.stats.branches_zeroed += 1;
+                        cx.eliminated.push(cx.path.clone());
+                        changed = true;
+                    }
                 } else {
                     // For each column, if it must be non-null, extract the expression's
                     // non-null requirements and include them too. We go in reverse order
                     // to ensure we squeegee down all requirements even for references to
                     // other columns produced in this operator.
-                    for column in (arity..(arity + scalars.len())).rev() {
-                        if columns.contains(&column) {
-                            scalars[column - arity].non_null_requirements(&mut columns);
+                    //
+                    // A scalar may reference only input columns and columns of scalars
+                    // before it, all of lower index, and so each column's requirements
+                    // are complete by the time it is visited, however long the chain
+                    // of references leading to it.
+                    for (index, scalar) in scalars.iter().enumerate().rev() {
+                        if columns.remove(&(arity + index)) {
+                            let origins = provenance.origins(arity + index);
+                            provenance.extend(&mut columns, &origins, |columns| {
+                                self.scalar_non_null_requirements(scalar, columns)
+                            });
                         }
-                        columns.remove(&column);
                     }
-                    self.action(input, columns, gets);
+                    provenance.retain(&columns);
+                    changed |=
+                        self.visit_child(0, input, columns, provenance, &known.inputs[0], cx)?;
                 }
             }
             RelationExpr::FlatMap {
                 input,
                 func,
                 exprs,
-                demand: _,
+                demand,
             } => {
+                // Columns produced by `func` are not columns of `input`, and
+                // their requirements must be translated to requirements on
+                // `exprs` instead. Each argument of a table function feeds
+                // each of its outputs, so `demand` cannot excuse any argument
+                // even if only some of the outputs are demanded.
+                //
+                // Requirements on the outputs are discarded whether or not
+                // `func` is empty on null input. A function with output on null
+                // input may have null or non-null outputs, and neither implies
+                // anything of its arguments; one without output on null input
+                // requires its arguments to be non-null regardless of which of
+                // its outputs are required.
+                let arity = known.inputs[0].arity();
+                columns.retain(|c| *c < arity);
+                provenance.retain(&columns);
                 if func.empty_on_null_input() {
-                    for expr in exprs {
-                        expr.non_null_requirements(&mut columns);
-                    }
+                    provenance.extend_from_plan(&mut columns, |columns| {
+                        for expr in exprs {
+                            self.scalar_non_null_requirements(expr, columns);
+                        }
+                    });
+                }
+                // The columns demanded were determined for the original input,
+                // and are left for `Demand` to determine anew.
+                let input_changed =
+                    self.visit_child(0, input, columns, provenance, &known.inputs[0], cx)?;
+                if input_changed {
+                    *demand = None;
                 }
-                self.action(input, columns, gets);
+                changed |= input_changed;
             }
             RelationExpr::Filter { input, predicates } => {
-                for predicate in predicates {
-                    predicate.non_null_requirements(&mut columns);
-                    // TODO: Not(IsNull) should add a constraint!
+                // A predicate that is literally false or null passes no rows,
+                // whatever the requirements, and one that is literally true
+                // imposes none.
+                let never_true = predicates
+                    .iter()
+                    .position(|p| p.is_literal_false() || p.is_literal_null());
+                match never_true {
+                    Some(index) if !self.analyze_only => {
+                        if cx.trace {
+                            log::warn!(
+                                "NonNullRequirements zeroed out a Filter: predicate {} is {}",
+                                index,
+                                predicates[index]
+                            );
+                        }
+                        relation.take_safely();
+                        cx.stats.branches_zeroed += 1;
+                        cx.eliminated.push(cx.path.clone());
+                        changed = true;
+                    }
+                    _ => {
+                        if !self.assume_all_non_strict {
+                            provenance.extend_from_plan(&mut columns, |columns| {
+                                let mut cache = RequirementsCache::default();
+                                for predicate in predicates.iter().filter(|p| !p.is_literal_true())
+                                {
+                                    predicate_non_null_requirements(predicate, columns, &mut cache);
+                                }
+                            });
+                        }
+                        changed |=
+                            self.visit_child(0, input, columns, provenance, &known.inputs[0], cx)?;
+                    }
+                }
+            }
+            // A join without requirements may still impose requirements
+            // through equivalences with non-nullable columns, but without
+            // equivalences there are none, and so no need to split them
+            // among the inputs.
+            RelationExpr::Join {
+                inputs,
+                equivalences,
+                implementation,
+                ..
+            } if columns.is_empty() && equivalences.is_empty() => {
+                let mut inputs_changed = false;
+                for (index, (input, known)) in inputs.iter_mut().zip(&known.inputs).enumerate() {
+                    inputs_changed |= self.visit_child(
+                        index,
+                        input,
+                        HashSet::new(),
+                        provenance.emptied(),
+                        known,
+                        cx,
+                    )?;
                 }
-                self.action(input, columns, gets);
+                reset_implementation(implementation, inputs_changed);
+                changed |= inputs_changed;
             }
             RelationExpr::Join {
                 inputs,
                 equivalences,
+                implementation,
                 ..
             } => {
-                let input_types = inputs.iter().map(|i| i.typ()).collect::<Vec<_>>();
+                // The columns proven non-null include those typed as such, as
+                // well as those that are non-null without being typed as such,
+                // e.g. outer keys filtered before a decorrelated subquery joins
+                // them with inner keys. Both are in the types of `known`.
+                let input_non_null = known
+                    .inputs
+                    .iter()
+                    .map(TypeTree::non_null_columns)
+                    .collect::<Vec<_>>();
 
-                let input_mapper = JoinInputMapper::new_from_input_types(&input_types);
+                let input_mapper = JoinInputMapper::new_from_arities(
+                    known.inputs.iter().map(TypeTree::arity).collect(),
+                );
 
                 let mut new_columns = input_mapper.split_column_set_by_input(&columns);
+                let mut new_provenance = (0..inputs.len())
+                    .map(|index| {
+                        provenance.renumber(|c| {
+                            let (col, rel) = input_mapper.map_column_to_local(c);
+                            Some(col).filter(|_| rel == index)
+                        })
+                    })
+                    .collect::<Vec<_>>();
 
                 // `variable` smears constraints around.
                 // Also, any non-nullable columns impose constraints on their equivalence class.
                 for equivalence in equivalences {
-                    let exists_constraint = equivalence.iter().any(|expr| {
-                        if let ScalarExpr::Column(c) = expr {
+                    // A non-null literal in the class constrains it as well,
+                    // as each member equals the literal.
+                    let exists_constraint = equivalence.iter().any(|expr| match expr {
+                        ScalarExpr::Column(c) => {
                             let (col, rel) = input_mapper.map_column_to_local(*c);
-                            new_columns[rel].contains(&col)
-                                || !input_types[rel].column_types[col].nullable
-                        } else {
-                            false
+                            new_columns[rel].contains(&col) || input_non_null[rel].contains(&col)
                         }
+                        ScalarExpr::Literal(..) => expr.is_literal_ok() && !expr.is_literal_null(),
+                        _ => false,
                     });
 
+                    // Each member of a constrained class must be non-null, and
+                    // so must any column on which a member is null-rejecting.
                     if exists_constraint {
+                        let mut constrained = Vec::new();
                         for expr in equivalence.iter() {
-                            if let ScalarExpr::Column(c) = expr {
-                                let (col, rel) = input_mapper.map_column_to_local(*c);
-                                new_columns[rel].insert(col);
+                            if self.assume_all_non_strict && !matches!(expr, ScalarExpr::Column(_))
+                            {
+                                continue;
+                            }
+                            for c in expr.support() {
+                                if expr.null_rejecting(c) {
+                                    constrained.push(input_mapper.map_column_to_local(c));
+                                }
+                            }
+                        }
+                        // The class is constrained by the origins of each of
+                        // its required members, and by the plan if a member is
+                        // non-null regardless.
+                        if provenance.is_tracked() {
+                            let mut origins = HashSet::new();
+                            for expr in equivalence.iter() {
+                                match expr {
+                                    ScalarExpr::Column(c) => {
+                                        let (col, rel) = input_mapper.map_column_to_local(*c);
+                                        if input_non_null[rel].contains(&col) {
+                                            origins.insert(Origin::Plan);
+                                        }
+                                        origins.extend(new_provenance[rel].origins(col));
+                                    }
+                                    ScalarExpr::Literal(..) => {
+                                        if expr.is_literal_ok() && !expr.is_literal_null() {
+                                            origins.insert(Origin::Plan);
+                                        }
+                                    }
+                                    _ => {}
+                                }
+                            }
+                            for (col, rel) in constrained.iter() {
+                                new_provenance[*rel].add(Some(*col), &origins);
                             }
                         }
+                        for (col, rel) in constrained {
+                            new_columns[rel].insert(col);
+                        }
                     }
                 }
 
-                for (input, columns) in inputs.iter_mut().zip(new_columns) {
-                    self.action(input, columns, gets);
+                let mut inputs_changed = false;
+                let inputs = inputs
+                    .iter_mut()
+                    .zip(new_columns)
+                    .zip(new_provenance)
+                    .zip(&known.inputs);
+                for (index, (((input, columns), provenance), known)) in inputs.enumerate() {
+                    inputs_changed |=
+                        self.visit_child(index, input, columns, provenance, known, cx)?;
                 }
+                reset_implementation(implementation, inputs_changed);
+                changed |= inputs_changed;
             }
             RelationExpr::Reduce {
                 input,
                 group_key,
                 aggregates,
+                // Discarding input rows never retracts any, and so leaves a
+                // monotonic input monotonic, even once it is empty.
                 monotonic: _,
-                expected_group_size: _,
+                expected_group_size,
             } => {
+                let width = group_key.len() + aggregates.len();
+                if let Some(column) = columns.iter().filter(|c| **c >= width).min() {
+                    return Err(crate::TransformError::InvalidInput {
+                        transform: self.name(),
+                        detail: format!(
+                            "non-null requirement on column {} of a reduction of arity {}",
+                            column, width
+                        ),
+                    });
+                }
                 let mut new_columns = HashSet::new();
+                let mut new_provenance = provenance.emptied();
+                let mut aggregate_required = false;
+                let mut aggregate_origins = HashSet::new();
                 for column in columns {
-                    // No obvious requirements on aggregate columns.
-                    // A "non-empty" requirement, I guess?
-                    if column < group_key.len() {
-                        group_key[column].non_null_requirements(&mut new_columns);
-                    }
-                    if column == group_key.len() && aggregates.len() == 1 {
-                        aggregates[0].expr.non_null_requirements(&mut new_columns);
+                    if let Some(key) = group_key.get(column) {
+                        let origins = provenance.origins(column);
+                        new_provenance.extend(&mut new_columns, &origins, |columns| {
+                            self.scalar_non_null_requirements(key, columns)
+                        });
+                    } else if let Some(aggregate) = aggregates.get(column - group_key.len()) {
+                        if null_on_null_inputs(&aggregate.func) {
+                            aggregate_required = true;
+                            aggregate_origins.extend(provenance.origins(column));
+                        }
                     }
                 }
-                self.action(input, new_columns, gets);
+                // A required aggregate that is null when all of its inputs are
+                // null requires only that *some* input of each group be non-null.
+                // Without a group key the whole input forms one group, whose
+                // rows may all be discarded: a reduction without keys has no
+                // output for an empty input, and so need not be known to be
+                // non-empty.
+                // We may nonetheless require each input row to be non-null, as
+                // long as no aggregate can observe the rows this discards: each
+                // aggregate must ignore null inputs and have a null input on each
+                // discarded row, which the common requirements of all aggregates
+                // ensure.
+                if aggregate_required && aggregates.iter().all(|a| ignores_null_inputs(&a.func)) {
+                    let requirements = aggregates
+                        .iter()
+                        .map(|aggregate| {
+                            let mut columns = HashSet::new();
+                            self.scalar_non_null_requirements(&aggregate.expr, &mut columns);
+                            columns
+                        })
+                        .collect();
+                    let common = intersect_column_sets(requirements);
+                    new_provenance.extend(&mut new_columns, &aggregate_origins, |columns| {
+                        columns.extend(common)
+                    });
+                }
+                // The expected size of each group was estimated for the rows
+                // of the original input, and no longer holds if any were
+                // discarded.
+                let input_changed =
+                    self.visit_child(0, input, new_columns, new_provenance, &known.inputs[0], cx)?;
+                if input_changed {
+                    *expected_group_size = None;
+                }
+                changed |= input_changed;
             }
-            RelationExpr::TopK { input, .. } => {
-                self.action(input, columns, gets);
+            RelationExpr::TopK {
+                input,
+                group_key,
+                limit,
+                offset,
+                ..
+            } => {
+                // Discarding the rows of a group may promote other rows of the
+                // group into its top rows, and so only requirements on the
+                // group key, which discard entire groups, may be pushed at the
+                // input. Without a limit or offset each row is retained.
+                if limit.is_some() || *offset > 0 {
+                    columns.retain(|c| group_key.contains(c));
+                    provenance.retain(&columns);
+                }
+                changed |= self.visit_child(0, input, columns, provenance, &known.inputs[0], cx)?;
             }
             RelationExpr::Negate { input } => {
-                self.action(input, columns, gets);
+                // Negation changes the multiplicity of each row but not its
+                // contents, so it has the requirements of its input. Rows that
+                // fail the requirements are discarded wherever they appear,
+                // and as a row with a null is never equal to one satisfying
+                // the requirements, they cannot cancel with rows we retain.
+                changed |= self.visit_child(0, input, columns, provenance, &known.inputs[0], cx)?;
             }
             RelationExpr::Threshold { input } => {
-                self.action(input, columns, gets);
+                // Threshold retains or discards each row based on its own
+                // multiplicity alone, so discarding input rows removes only
+                // those same rows from its output.
+                changed |= self.visit_child(0, input, columns, provenance, &known.inputs[0], cx)?;
             }
             RelationExpr::Union { base, inputs } => {
-                self.action(base, columns.clone(), gets);
-                for input in inputs {
-                    self.action(input, columns.clone(), gets);
+                // Each branch has the arity and column meaning of `base`, which
+                // is what allows the same requirements to be pushed at each of
+                // them. The requirements of the union are those of each branch,
+                // even if some branches would satisfy them on their own; see
+                // `union_non_null_columns` for the columns that do.
+                //
+                // Each branch is visited on its own, and one that is pruned to
+                // empty leaves the others as they are. The requirements are
+                // moved rather than cloned into the last branch, which for a
+                // union of `base` alone is the only one.
+                debug_assert!(known
+                    .inputs
+                    .iter()
+                    .all(|input| input.arity() == known.arity()));
+                let branches = 1 + inputs.len();
+                let inputs = std::iter::once(&mut **base).chain(inputs.iter_mut());
+                for (index, (input, known)) in inputs.zip(&known.inputs).enumerate() {
+                    let (columns, provenance) = if index + 1 < branches {
+                        (columns.clone(), provenance.clone())
+                    } else {
+                        (
+                            std::mem::take(&mut columns),
+                            std::mem::take(&mut provenance),
+                        )
+                    };
+                    changed |= self.visit_child(index, input, columns, provenance, known, cx)?;
+                }
+            }
+            RelationExpr::ArrangeBy { input, keys } => {
+                // An arrangement presents its input rows unchanged, including
+                // those whose keys are null, which are arranged like any other
+                // key. Joins equate null keys too, and impose requirements only
+                // through their equivalences, in the `Join` arm. The
+                // requirements of an arrangement are then those of its output
+                // columns alone.
+                let input_changed =
+                    self.visit_child(0, input, columns, provenance, &known.inputs[0], cx)?;
+                // Zeroing out keeps the arity of what it empties, and so the
+                // keys of an arrangement remain valid for its changed input.
+                // Should they not, an arrangement by no keys is well-formed
+                // where one by the keys would not be.
+                if input_changed {
+                    let arity = known.inputs[0].arity();
+                    if keys
+                        .iter()
+                        .flatten()
+                        .any(|key| key.support().iter().any(|c| *c >= arity))
+                    {
+                        keys.clear();
+                    }
                 }
+                changed |= input_changed;
             }
-            RelationExpr::ArrangeBy { input, .. } => {
-                self.action(input, columns, gets);
+        }
+        Ok(changed)
+    }
+}
+
+/// Describes the zeroing out of a `Map`, of input arity `arity`, whose
+/// required `column` is the literal null `scalar`.
+fn zeroed_map_message(column: usize, arity: usize, scalar: &ScalarExpr) -> String {
+    let description = match scalar {
+        ScalarExpr::Literal(_, typ) => format!("a null {:?}", typ.scalar_type),
+        _ => "a null".into(),
+    };
+    format!(
+        "NonNullRequirements zeroed out a Map: column {} must be non-null, \
+         but scalar {} is {}",
+        column,
+        column - arity,
+        description
+    )
+}
+
+/// The name of the operator at the root of `relation`, for diagnostics.
+fn operator_name(relation: &RelationExpr) -> &'static str {
+    match relation {
+        RelationExpr::Constant { .. } => "Constant",
+        RelationExpr::Get { .. } => "Get",
+        RelationExpr::Let { .. } => "Let",
+        RelationExpr::Project { .. } => "Project",
+        RelationExpr::Map { .. } => "Map",
+        RelationExpr::FlatMap { .. } => "FlatMap",
+        RelationExpr::Filter { .. } => "Filter",
+        RelationExpr::Join { .. } => "Join",
+        RelationExpr::Reduce { .. } => "Reduce",
+        RelationExpr::TopK { .. } => "TopK",
+        RelationExpr::Negate { .. } => "Negate",
+        RelationExpr::Threshold { .. } => "Threshold",
+        RelationExpr::Union { .. } => "Union",
+        RelationExpr::ArrangeBy { .. } => "ArrangeBy",
+    }
+}
+
+/// Alternates `NonNullRequirements` with predicate pushdown and constant
+/// folding until none of them changes `relation`.
+///
+/// Each may expose opportunities for the others, e.g. folding a scalar to a
+/// literal null that `NonNullRequirements` can then zero out, which a single
+/// application of each would leave behind. Fails if `args.fuel` runs out, or
+/// if the transforms do not settle.
+pub fn non_null_fixpoint(
+    relation: &mut RelationExpr,
+    args: TransformArgs,
+) -> Result<(), crate::TransformError> {
+    let fixpoint = crate::Fixpoint {
+        transforms: vec![
+            Box::new(NonNullRequirements::default()),
+            Box::new(crate::predicate_pushdown::PredicatePushdown),
+            Box::new(crate::reduction::FoldConstants),
+        ],
+        limit: 100,
+    };
+    crate::Transform::transform(&fixpoint, relation, args)
+}
+
+/// Describes the changes `NonNullRequirements` made in turning `before` into
+/// `after`, one per changed subexpression, for use in debugging.
+///
+/// Subexpressions are identified by their path from the root, as for
+/// `NonNullRequirements::action_at_path`. Only the changes the transform makes
+/// are recognized: pruning the rows of a constant, and replacing an expression
+/// by an empty constant. Other differences are reported as unexplained.
+pub fn explain_changes(before: &RelationExpr, after: &RelationExpr) -> Vec<String> {
+    let mut changes = Vec::new();
+    changes_at(before, after, &mut vec![], &mut changes);
+    changes
+        .into_iter()
+        .map(|(path, change)| match change {
+            Change::Pruned { pruned } => {
+                format!("pruned {} rows from constant at path {:?}", pruned, path)
+            }
+            Change::Zeroed => format!("zeroed subtree at path {:?}", path),
+            Change::Unexplained => format!("unexplained change at path {:?}", path),
+        })
+        .collect()
+}
+
+/// A change `NonNullRequirements` made to a subexpression.
+enum Change {
+    /// Rows of a constant were pruned.
+    Pruned { pruned: usize },
+    /// The subexpression was replaced by an empty constant.
+    Zeroed,
+    /// Any other change.
+    Unexplained,
+}
+
+/// Adds to `changes` those made in turning `before` into `after`, whose
+/// path is `path`, each with the path of the changed subexpression.
+fn changes_at(
+    before: &RelationExpr,
+    after: &RelationExpr,
+    path: &mut Vec<usize>,
+    changes: &mut Vec<(Vec<usize>, Change)>,
+) {
+    if before == after {
+        return;
+    }
+    match (before, after) {
+        (
+            RelationExpr::Constant { rows: before, .. },
+            RelationExpr::Constant { rows: after, .. },
+        ) if after.len() < before.len() => changes.push((
+            path.clone(),
+            Change::Pruned {
+                pruned: before.len() - after.len(),
+            },
+        )),
+        (_, RelationExpr::Constant { rows, .. }) if rows.is_empty() => {
+            changes.push((path.clone(), Change::Zeroed))
+        }
+        _ => {
+            let mut before_children = Vec::new();
+            before.visit1(|child| before_children.push(child));
+            let mut after_children = Vec::new();
+            after.visit1(|child| after_children.push(child));
+            if operator_name(before) != operator_name(after)
+                || before_children.len() != after_children.len()
+            {
+                changes.push((path.clone(), Change::Unexplained));
+                return;
+            }
+            let changes_before = changes.len();
+            for (index, (before, after)) in
+                before_children.into_iter().zip(after_children).enumerate()
+            {
+                path.push(index);
+                changes_at(before, after, path, changes);
+                path.pop();
+            }
+            // The operator changed, other than through its children, e.g. a
+            // join whose implementation was discarded as its inputs changed.
+            if changes.len() == changes_before {
+                changes.push((path.clone(), Change::Unexplained));
+            }
+        }
+    }
+}
+
+/// Records in `report` whether each key of each arrangement in `relation` is
+/// proven non-null, by the columns that `non_null_columns` proves non-null in
+/// the arranged input, as `known` holds them.
+///
+/// Arrangements are recorded in post-order, as `RelationExpr::visit` visits
+/// them.
+fn record_arrangements(
+    relation: &RelationExpr,
+    known: &TypeTree,
+    report: &mut SourceRequirementReport,
+) {
+    let mut inputs = known.inputs.iter();
+    relation.visit1(|input| {
+        if let Some(known) = inputs.next() {
+            record_arrangements(input, known, report);
+        }
+    });
+    if let RelationExpr::ArrangeBy { keys, .. } = relation {
+        let typ = &known.inputs[0].typ;
+        let non_null = keys
+            .iter()
+            .map(|key| key.iter().all(|expr| !expr.typ(typ).nullable))
+            .collect();
+        report.arrangements.push(ArrangementNullability {
+            keys: keys.clone(),
+            non_null,
+        });
+    }
+}
+
+/// Appends the requirements of each reference in `other` to those in `gets`.
+fn extend_gets(
+    gets: &mut HashMap<Id, Vec<HashSet<usize>>>,
+    other: HashMap<Id, Vec<HashSet<usize>>>,
+) {
+    for (id, needs) in other {
+        gets.entry(id).or_insert_with(Vec::new).extend(needs);
+    }
+}
+
+/// Discards the implementation chosen for a join if its inputs changed.
+///
+/// The implementation was planned around the arrangements of the original
+/// inputs, which may no longer be the right ones; `JoinImplementation` will
+/// plan the join anew.
+fn reset_implementation(implementation: &mut JoinImplementation, inputs_changed: bool) {
+    if inputs_changed {
+        *implementation = JoinImplementation::Unimplemented;
+    }
+}
+
+/// Adds to `columns` those columns that must be non-null for `predicate` to pass.
+///
+/// Requirements of repeated subexpressions are looked up in `cache`.
+fn predicate_non_null_requirements<'a>(
+    predicate: &'a ScalarExpr,
+    columns: &mut HashSet<usize>,
+    cache: &mut RequirementsCache<'a>,
+) {
+    match predicate {
+        // `a AND b` passes only if both `a` and `b` would pass.
+        ScalarExpr::CallBinary {
+            func: BinaryFunc::And,
+            expr1,
+            expr2,
+        } => {
+            predicate_non_null_requirements(expr1, columns, cache);
+            predicate_non_null_requirements(expr2, columns, cache);
+            return;
+        }
+        // `a OR b` passes if either `a` or `b` would, and so requires only
+        // what both of them do.
+        ScalarExpr::CallBinary {
+            func: BinaryFunc::Or,
+            expr1,
+            expr2,
+        } => {
+            let mut columns1 = HashSet::new();
+            predicate_non_null_requirements(expr1, &mut columns1, cache);
+            let mut columns2 = HashSet::new();
+            predicate_non_null_requirements(expr2, &mut columns2, cache);
+            columns.extend(columns1.intersection(&columns2));
+            return;
+        }
+        _ => {}
+    }
+    cache.non_null_requirements(predicate, columns);
+    // A predicate `!isnull(expr)` can only pass if `expr` is non-null, and so
+    // imposes the requirements of `expr`.
+    if let ScalarExpr::CallUnary {
+        func: UnaryFunc::Not,
+        expr,
+    } = predicate
+    {
+        if let ScalarExpr::CallUnary {
+            func: UnaryFunc::IsNull,
+            expr,
+        } = &**expr
+        {
+            cache.non_null_requirements(expr, columns);
+        }
+    }
+}
+
+/// Memoizes `ScalarExpr::non_null_requirements` for the expressions of a
+/// single operator.
+///
+/// Requirements name columns of the input of the operator, and so must not be
+/// shared with other operators.
+#[derive(Default)]
+struct RequirementsCache<'a> {
+    requirements: HashMap<&'a ScalarExpr, HashSet<usize>>,
+    /// The number of requirements not found in `requirements`.
+    misses: usize,
+}
+
+impl<'a> RequirementsCache<'a> {
+    /// Adds the non-null requirements of `expr` to `columns`.
+    fn non_null_requirements(&mut self, expr: &'a ScalarExpr, columns: &mut HashSet<usize>) {
+        let misses = &mut self.misses;
+        let requirements = self.requirements.entry(expr).or_insert_with(|| {
+            *misses += 1;
+            let mut columns = HashSet::new();
+            expr.non_null_requirements(&mut columns);
+            columns
+        });
+        columns.extend(requirements.iter().copied());
+    }
+}
+
+/// True if `func` ignores its null inputs, so that removing them does not
+/// change its result.
+fn ignores_null_inputs(func: &AggregateFunc) -> bool {
+    !matches!(
+        func,
+        AggregateFunc::Any | AggregateFunc::All | AggregateFunc::Dummy
+    )
+}
+
+/// True if `func` is null when all of its inputs are null.
+///
+/// Notably `count` is never null, and so imposes no requirements.
+///
+/// An aggregate not listed here imposes no requirements, which is the safe
+/// default for any aggregate added later. One that may be null for reasons
+/// other than its inputs, as a window function like `lag` is for the first
+/// row of its partition, must stay unlisted.
+fn null_on_null_inputs(func: &AggregateFunc) -> bool {
+    use AggregateFunc::*;
+    matches!(
+        func,
+        MaxInt32
+            | MaxInt64
+            | MaxFloat32
+            | MaxFloat64
+            | MaxDecimal
+            | MaxBool
+            | MaxString
+            | MaxDate
+            | MaxTimestamp
+            | MaxTimestampTz
+            | MinInt32
+            | MinInt64
+            | MinFloat32
+            | MinFloat64
+            | MinDecimal
+            | MinBool
+            | MinString
+            | MinDate
+            | MinTimestamp
+            | MinTimestampTz
+            | SumInt32
+            | SumInt64
+            | SumFloat32
+            | SumFloat64
+            | SumDecimal
+    )
+}
+
+/// Columns of a union that are non-null in every one of its branches.
+///
+/// The branches of a union must agree on their arity, and a column of the
+/// union is non-null exactly when it is non-null in each branch.
+pub fn union_non_null_columns(base: &RelationExpr, inputs: &[RelationExpr]) -> HashSet<usize> {
+    let mut columns = non_nullable_columns(&base.typ());
+    for input in inputs {
+        let input_columns = non_nullable_columns(&input.typ());
+        columns.retain(|c| input_columns.contains(c));
+    }
+    columns
+}
+
+/// Columns of `relation` that are non-null in each of its rows.
+///
+/// This is the dual of `NonNullRequirements`: rather than push requirements
+/// toward the sources, it derives guarantees from the sources toward the
+/// root. The columns include at least those that `relation.typ()` reports as
+/// non-nullable.
+///
+/// The columns are derived in a single pass over `relation`. A malformed
+/// `relation`, e.g. with a scalar that references a column its input lacks,
+/// has no columns reported.
+pub fn non_null_columns(relation: &RelationExpr) -> HashSet<usize> {
+    match TypeTree::derive(relation, true, usize::MAX) {
+        Ok(known) => known.non_null_columns(),
+        Err(_) => HashSet::new(),
+    }
+}
+
+/// True if `relation` ensures that `column` is non-null in each of its rows,
+/// whether by its type or by the predicates and equivalences of `relation`.
+///
+/// This reports whether `column` is among those that `non_null_columns`
+/// derives, and so whether the plan already ensures the column is non-null,
+/// rather than whether requiring it would prune anything. Columns beyond the
+/// arity of `relation` are never reported.
+pub fn requires_column_non_null(relation: &RelationExpr, column: usize) -> bool {
+    non_null_columns(relation).contains(&column)
+}
+
+/// The column types of an expression and of each of its subexpressions.
+///
+/// Deriving these once, bottom-up, spares each operator from determining the
+/// types of its inputs, which would take time quadratic in the depth of the
+/// plan. `inputs` holds the trees of the operator's children, in the order
+/// that `RelationExpr::visit1` visits them.
+#[derive(Debug)]
+pub(crate) struct TypeTree {
+    pub(crate) typ: RelationType,
+    pub(crate) inputs: Vec<TypeTree>,
+}
+
+impl TypeTree {
+    /// Derives the column types of `relation` and its subexpressions.
+    ///
+    /// If `refine` is set, columns that `relation` proves non-null, whether by
+    /// its filters, its join equivalences, or the rows of its constants, are
+    /// typed non-nullable even if `relation.typ()` reports them nullable.
+    /// Only the column types are derived, and not the keys.
+    pub(crate) fn derive(
+        relation: &RelationExpr,
+        refine: bool,
+        recursion_limit: usize,
+    ) -> Result<Self, crate::TransformError> {
+        Self::derive_in(relation, refine, recursion_limit, 0, &mut HashMap::new())
+    }
+
+    /// Derives the column types of `relation`, where `lets` holds those of
+    /// each bound identifier.
+    fn derive_in(
+        relation: &RelationExpr,
+        refine: bool,
+        recursion_limit: usize,
+        depth: usize,
+        lets: &mut HashMap<Id, RelationType>,
+    ) -> Result<Self, crate::TransformError> {
+        if depth > recursion_limit {
+            return Err(crate::TransformError::Internal(
+                "recursion limit exceeded".into(),
+            ));
+        }
+        let derive =
+            |input: &RelationExpr, lets: &mut HashMap<Id, RelationType>| -> Result<Self, _> {
+                Self::derive_in(input, refine, recursion_limit, depth + 1, lets)
+            };
+        let (typ, inputs) = match relation {
+            RelationExpr::Constant { rows, typ } => {
+                let mut typ = RelationType::new(typ.column_types.clone());
+                if refine {
+                    let mut has_null = vec![false; typ.arity()];
+                    for (row, _) in rows {
+                        for (datum, has_null) in row.iter().zip(has_null.iter_mut()) {
+                            *has_null |= datum.is_null();
+                        }
+                    }
+                    for (column_typ, has_null) in typ.column_types.iter_mut().zip(has_null) {
+                        column_typ.nullable &= has_null;
+                    }
+                }
+                (typ, Vec::new())
+            }
+            RelationExpr::Get { id, typ } => {
+                let mut typ = RelationType::new(typ.column_types.clone());
+                if let Some(known) = lets.get(id) {
+                    for (column_typ, known) in typ.column_types.iter_mut().zip(&known.column_types)
+                    {
+                        column_typ.nullable &= known.nullable;
+                    }
+                }
+                (typ, Vec::new())
+            }
+            RelationExpr::Let { id, value, body } => {
+                let id = Id::Local(*id);
+                let value = derive(value, lets)?;
+                // Only refined types say more of a `Get` than its own type.
+                let prior = if refine {
+                    lets.insert(id, value.typ.clone())
+                } else {
+                    None
+                };
+                let body = derive(body, lets);
+                match prior {
+                    Some(prior) => lets.insert(id, prior),
+                    None => lets.remove(&id),
+                };
+                let body = body?;
+                (body.typ.clone(), vec![value, body])
+            }
+            RelationExpr::Project { input, outputs } => {
+                let input = derive(input, lets)?;
+                let column_types = outputs
+                    .iter()
+                    .map(|c| {
+                        input
+                            .typ
+                            .column_types
+                            .get(*c)
+                            .cloned()
+                            .ok_or_else(|| out_of_range(relation, *c, input.arity()))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                (RelationType::new(column_types), vec![input])
+            }
+            RelationExpr::Map { input, scalars } => {
+                let input = derive(input, lets)?;
+                let mut typ = input.typ.clone();
+                for scalar in scalars {
+                    // Each scalar may reference the columns of the input and
+                    // those of the scalars before it.
+                    check_support(relation, scalar, typ.arity())?;
+                    let column_typ = scalar.typ(&typ);
+                    typ.column_types.push(column_typ);
+                }
+                (typ, vec![input])
+            }
+            RelationExpr::FlatMap {
+                input, func, exprs, ..
+            } => {
+                let input = derive(input, lets)?;
+                let mut typ = input.typ.clone();
+                // A null argument produces no rows, and so no row has a column
+                // that the arguments require to be non-null be null.
+                if refine && func.empty_on_null_input() {
+                    let mut columns = HashSet::new();
+                    for expr in exprs {
+                        expr.non_null_requirements(&mut columns);
+                    }
+                    mark_non_null(&mut typ, &columns);
+                }
+                typ.column_types.extend(func.output_type().column_types);
+                (typ, vec![input])
+            }
+            RelationExpr::Filter { input, predicates } => {
+                let input = derive(input, lets)?;
+                let mut typ = input.typ.clone();
+                if refine {
+                    let mut columns = HashSet::new();
+                    let mut cache = RequirementsCache::default();
+                    for predicate in predicates {
+                        predicate_non_null_requirements(predicate, &mut columns, &mut cache);
+                    }
+                    mark_non_null(&mut typ, &columns);
+                }
+                (typ, vec![input])
+            }
+            RelationExpr::Join {
+                inputs,
+                equivalences,
+                ..
+            } => {
+                let inputs = inputs
+                    .iter()
+                    .map(|input| derive(input, lets))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let mut typ = RelationType::new(
+                    inputs
+                        .iter()
+                        .flat_map(|input| input.typ.column_types.iter().cloned())
+                        .collect(),
+                );
+                // Equated columns have equal values, and so are all non-null
+                // if any one of them is.
+                if refine {
+                    for equivalence in equivalences {
+                        let exists_non_null = equivalence.iter().any(|expr| match expr {
+                            ScalarExpr::Column(c) => typ
+                                .column_types
+                                .get(*c)
+                                .map_or(false, |column_typ| !column_typ.nullable),
+                            ScalarExpr::Literal(..) => {
+                                expr.is_literal_ok() && !expr.is_literal_null()
+                            }
+                            _ => false,
+                        });
+                        if exists_non_null {
+                            let columns = equivalence
+                                .iter()
+                                .filter_map(|expr| match expr {
+                                    ScalarExpr::Column(c) => Some(*c),
+                                    _ => None,
+                                })
+                                .collect();
+                            mark_non_null(&mut typ, &columns);
+                        }
+                    }
+                }
+                (typ, inputs)
+            }
+            RelationExpr::Reduce {
+                input,
+                group_key,
+                aggregates,
+                ..
+            } => {
+                let input = derive(input, lets)?;
+                let arity = input.arity();
+                let mut column_types = Vec::with_capacity(group_key.len() + aggregates.len());
+                for key in group_key {
+                    check_support(relation, key, arity)?;
+                    column_types.push(key.typ(&input.typ));
+                }
+                for aggregate in aggregates {
+                    check_support(relation, &aggregate.expr, arity)?;
+                    let mut column_typ = aggregate.typ(&input.typ);
+                    // Each group has at least one row, and so only `count` or
+                    // an aggregate of a null argument could be null.
+                    if refine {
+                        column_typ.nullable &= match aggregate.func {
+                            AggregateFunc::Count => false,
+                            AggregateFunc::Dummy => true,
+                            _ => aggregate.expr.typ(&input.typ).nullable,
+                        };
+                    }
+                    column_types.push(column_typ);
+                }
+                (RelationType::new(column_types), vec![input])
+            }
+            RelationExpr::TopK { input, .. }
+            | RelationExpr::Negate { input }
+            | RelationExpr::Threshold { input }
+            | RelationExpr::ArrangeBy { input, .. } => {
+                let input = derive(input, lets)?;
+                (input.typ.clone(), vec![input])
+            }
+            RelationExpr::Union { base, inputs } => {
+                let base = derive(base, lets)?;
+                let mut typ = base.typ.clone();
+                let mut branches = vec![base];
+                for input in inputs {
+                    let input = derive(input, lets)?;
+                    for (column_typ, input_typ) in
+                        typ.column_types.iter_mut().zip(&input.typ.column_types)
+                    {
+                        column_typ.nullable |= input_typ.nullable;
+                    }
+                    branches.push(input);
+                }
+                (typ, branches)
+            }
+        };
+        Ok(TypeTree { typ, inputs })
+    }
+
+    /// The number of columns of the expression.
+    fn arity(&self) -> usize {
+        self.typ.arity()
+    }
+
+    /// Columns of the expression that are typed non-nullable.
+    pub(crate) fn non_null_columns(&self) -> HashSet<usize> {
+        non_nullable_columns(&self.typ)
+    }
+}
+
+/// Marks each of `columns` of `typ` non-nullable, ignoring any beyond its
+/// arity.
+fn mark_non_null(typ: &mut RelationType, columns: &HashSet<usize>) {
+    for column in columns {
+        if let Some(column_typ) = typ.column_types.get_mut(*column) {
+            column_typ.nullable = false;
+        }
+    }
+}
+
+/// Errors if `expr`, a scalar of `relation`, references a column beyond
+/// `arity`, the number of columns it may reference.
+fn check_support(
+    relation: &RelationExpr,
+    expr: &ScalarExpr,
+    arity: usize,
+) -> Result<(), crate::TransformError> {
+    match expr.support().into_iter().filter(|c| *c >= arity).min() {
+        Some(column) => Err(out_of_range(relation, column, arity)),
+        None => Ok(()),
+    }
+}
+
+/// The error for a reference by `relation` to `column` of an input of
+/// `arity` columns.
+fn out_of_range(relation: &RelationExpr, column: usize, arity: usize) -> crate::TransformError {
+    crate::TransformError::InvalidInput {
+        transform: "NonNullRequirements",
+        detail: format!(
+            "{} references column {} of an input of arity {}",
+            operator_name(relation),
+            column,
+            arity
+        ),
+    }
+}
+
+/// Columns that `typ` reports as non-nullable.
+fn non_nullable_columns(typ: &RelationType) -> HashSet<usize> {
+    typ.column_types
+        .iter()
+        .enumerate()
+        .filter(|(_, typ)| !typ.nullable)
+        .map(|(column, _)| column)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use std::time::{Duration, Instant};
+
+    use expr::{
+        AggregateExpr, AggregateFunc, BinaryFunc, GlobalId, Id, JoinImplementation, LocalId,
+        RelationExpr, ScalarExpr, TableFunc, UnaryFunc, VariadicFunc,
+    };
+    use repr::{Datum, RelationType, ScalarType};
+
+    use super::{
+        non_null_columns, non_null_fixpoint, predicate_non_null_requirements,
+        requires_column_non_null, NonNullRequirements, RequirementsCache,
+    };
+    use crate::test_util::int64_constant;
+    use crate::testing::assert_constant_rows_retained;
+    use crate::{
+        ArrangementNullability, SourceRequirementReport, Transform, TransformArgs, TransformError,
+        TransformStats,
+    };
+
+    fn int64_typ(arity: usize) -> RelationType {
+        RelationType::new(vec![ScalarType::Int64.nullable(true); arity])
+    }
+
+    fn non_null_int64_typ(arity: usize) -> RelationType {
+        RelationType::new(vec![ScalarType::Int64.nullable(false); arity])
+    }
+
+    fn null_int64() -> ScalarExpr {
+        ScalarExpr::literal_null(ScalarType::Int64.nullable(true))
+    }
+
+    fn is_not_null(expr: ScalarExpr) -> ScalarExpr {
+        expr.call_unary(UnaryFunc::IsNull)
+            .call_unary(UnaryFunc::Not)
+    }
+
+    fn get(id: u64, typ: RelationType) -> RelationExpr {
+        RelationExpr::Get {
+            id: Id::Global(GlobalId::User(id)),
+            typ,
+        }
+    }
+
+    fn empty(typ: RelationType) -> RelationExpr {
+        RelationExpr::Constant { rows: vec![], typ }
+    }
+
+    fn run(relation: &mut RelationExpr) {
+        let before = relation.clone();
+        NonNullRequirements::default()
+            .action(
+                relation,
+                HashSet::new(),
+                &mut HashMap::new(),
+                &mut None,
+                &mut Default::default(),
+            )
+            .unwrap();
+        assert_constant_rows_retained(&before, relation);
+    }
+
+    #[test]
+    fn filter_is_not_null_prunes_constant() {
+        let rows = vec![
+            vec![Datum::Int64(1), Datum::Null],
+            vec![Datum::Null, Datum::Int64(2)],
+            vec![Datum::Int64(3), Datum::Int64(4)],
+        ];
+        let predicates = vec![is_not_null(ScalarExpr::Column(0))];
+        let mut relation = RelationExpr::constant(rows, int64_typ(2)).filter(predicates.clone());
+        run(&mut relation);
+
+        let rows = vec![
+            vec![Datum::Int64(1), Datum::Null],
+            vec![Datum::Int64(3), Datum::Int64(4)],
+        ];
+        assert_eq!(
+            relation,
+            RelationExpr::constant(rows, int64_typ(2)).filter(predicates)
+        );
+    }
+
+    #[test]
+    fn fuel_bounds_nested_unions() {
+        // Each level adds a union and a constant, for `1 + 2 * depth` nodes.
+        let depth = 50;
+        let mut relation = empty(int64_typ(1));
+        for _ in 0..depth {
+            relation = relation.union(empty(int64_typ(1)));
+        }
+        let transform = |relation: &mut RelationExpr, fuel| {
+            NonNullRequirements::default().transform(
+                relation,
+                TransformArgs {
+                    fuel,
+                    ..TransformArgs::new(&mut Default::default(), &HashMap::new())
+                },
+            )
+        };
+        assert!(matches!(
+            transform(&mut relation.clone(), Some(2 * depth)),
+            Err(TransformError::Exhausted)
+        ));
+        assert!(transform(&mut relation.clone(), Some(2 * depth + 1)).is_ok());
+        assert!(transform(&mut relation, None).is_ok());
+    }
+
+    #[test]
+    fn deadline_bounds_wide_unions() {
+        let relation = RelationExpr::Union {
+            base: Box::new(empty(int64_typ(1))),
+            inputs: vec![empty(int64_typ(1)); 1000],
+        };
+        let transform = |relation: &mut RelationExpr, deadline| {
+            NonNullRequirements::default().transform(
+                relation,
+                TransformArgs {
+                    deadline,
+                    ..TransformArgs::new(&mut Default::default(), &HashMap::new())
+                },
+            )
+        };
+        // The deadline is checked on the first visit, and so even a deadline
+        // that has just passed is noticed.
+        assert!(matches!(
+            transform(&mut relation.clone(), Some(Instant::now())),
+            Err(TransformError::Timeout)
+        ));
+        let generous = Instant::now() + Duration::from_secs(3600);
+        assert!(transform(&mut relation.clone(), Some(generous)).is_ok());
+        assert!(transform(&mut relation, None).is_ok());
+    }
+
+    fn columns(columns: &[usize]) -> HashSet<usize> {
+        columns.iter().cloned().collect()
+    }
+
+    #[test]
+    fn non_null_columns_constant_and_get() {
+        let constant = RelationExpr::constant(
+            vec![
+                vec![Datum::Int64(1), Datum::Null],
+                vec![Datum::Int64(2), Datum::Int64(3)],
+            ],
+            int64_typ(2),
+        );
+        assert_eq!(non_null_columns(&constant), columns(&[0]));
+        assert_eq!(non_null_columns(&empty(int64_typ(2))), columns(&[0, 1]));
+        assert_eq!(non_null_columns(&get(1, int64_typ(2))), columns(&[]));
+        assert_eq!(
+            non_null_columns(&get(1, non_null_int64_typ(2))),
+            columns(&[0, 1])
+        );
+    }
+
+    #[test]
+    fn non_null_columns_let() {
+        let id = LocalId::new(0);
+        let value = RelationExpr::constant(vec![vec![Datum::Int64(1), Datum::Null]], int64_typ(2));
+        let body = RelationExpr::Get {
+            id: Id::Local(id),
+            typ: int64_typ(2),
+        };
+        let relation = RelationExpr::Let {
+            id,
+            value: Box::new(value),
+            body: Box::new(body.clone().union(body)),
+        };
+        assert_eq!(non_null_columns(&relation), columns(&[0]));
+    }
+
+    #[test]
+    fn non_null_columns_project_and_map() {
+        let input = RelationExpr::constant(vec![vec![Datum::Int64(1), Datum::Null]], int64_typ(2));
+        assert_eq!(
+            non_null_columns(&input.clone().project(vec![1, 0, 0])),
+            columns(&[1, 2])
+        );
+        let relation = input.map(vec![
+            ScalarExpr::column(0).call_binary(ScalarExpr::column(0), BinaryFunc::AddInt64),
+            ScalarExpr::column(1).call_binary(ScalarExpr::column(0), BinaryFunc::AddInt64),
+            ScalarExpr::column(2).call_unary(UnaryFunc::IsNull),
+        ]);
+        assert_eq!(non_null_columns(&relation), columns(&[0, 2, 4]));
+    }
+
+    #[test]
+    fn non_null_columns_flat_map() {
+        // Rows with a null argument produce no output.
+        let relation = get(1, int64_typ(3)).flat_map(
+            TableFunc::GenerateSeriesInt64,
+            vec![ScalarExpr::column(0), ScalarExpr::column(1)],
+        );
+        assert_eq!(non_null_columns(&relation), columns(&[0, 1, 3]));
+    }
+
+    #[test]
+    fn non_null_columns_filter() {
+        let relation = get(1, int64_typ(3)).filter(vec![
+            is_not_null(ScalarExpr::column(0)),
+            ScalarExpr::column(1).call_binary(ScalarExpr::column(1), BinaryFunc::Eq),
+        ]);
+        assert_eq!(non_null_columns(&relation), columns(&[0, 1]));
+    }
+
+    #[test]
+    fn non_null_columns_join_and_filter() {
+        // The filter makes `#0` non-null, and the join equates it with `#2`.
+        let relation = RelationExpr::join_scalars(
+            vec![
+                get(1, int64_typ(2)).filter(vec![is_not_null(ScalarExpr::column(0))]),
+                get(2, int64_typ(2)),
+            ],
+            vec![vec![ScalarExpr::column(0), ScalarExpr::column(2)]],
+        );
+        assert_eq!(non_null_columns(&relation), columns(&[0, 2]));
+        assert_eq!(
+            non_null_columns(&relation.filter(vec![is_not_null(ScalarExpr::column(3))])),
+            columns(&[0, 2, 3])
+        );
+    }
+
+    #[test]
+    fn non_null_columns_reduce() {
+        let input = RelationExpr::constant(
+            vec![
+                vec![Datum::Int64(1), Datum::Int64(2), Datum::Null],
+                vec![Datum::Int64(1), Datum::Int64(3), Datum::Null],
+            ],
+            int64_typ(3),
+        );
+        let aggregate = |func, column| AggregateExpr {
+            func,
+            expr: ScalarExpr::column(column),
+            distinct: false,
+        };
+        let relation = input.reduce(
+            vec![0, 2],
+            vec![
+                aggregate(AggregateFunc::MinInt64, 1),
+                aggregate(AggregateFunc::MinInt64, 2),
+                aggregate(AggregateFunc::Count, 2),
+            ],
+            None,
+        );
+        assert_eq!(non_null_columns(&relation), columns(&[0, 2, 4]));
+    }
+
+    #[test]
+    fn non_null_columns_union_and_unary() {
+        let left = RelationExpr::constant(vec![vec![Datum::Int64(1), Datum::Null]], int64_typ(2));
+        let right =
+            RelationExpr::constant(vec![vec![Datum::Int64(1), Datum::Int64(2)]], int64_typ(2));
+        assert_eq!(
+            non_null_columns(&right.clone().negate().threshold()),
+            columns(&[0, 1])
+        );
+        assert_eq!(non_null_columns(&left.union(right)), columns(&[0]));
+    }
+
+    #[test]
+    fn let_with_many_references() {
+        let id = LocalId::new(0);
+        let typ = int64_typ(3);
+        let rows = (0..8)
+            .map(|i| {
+                (0..3)
+                    .map(|c| {
+                        if i & (1 << c) == 0 {
+                            Datum::Null
+                        } else {
+                            Datum::Int64(i)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let requirements = (0..50)
+            .map(|i| match i % 3 {
+                0 => vec![0, 1],
+                1 => vec![0, 2],
+                _ => vec![0, 1, 2],
+            })
+            .collect::<Vec<_>>();
+        let relation = |rows| RelationExpr::Let {
+            id,
+            value: Box::new(RelationExpr::constant(rows, typ.clone())),
+            body: Box::new(RelationExpr::union_many(
+                requirements
+                    .iter()
+                    .map(|columns| {
+                        RelationExpr::Get {
+                            id: Id::Local(id),
+                            typ: typ.clone(),
+                        }
+                        .filter(
+                            columns
+                                .iter()
+                                .map(|c| is_not_null(ScalarExpr::column(*c)))
+                                .collect(),
+                        )
+                    })
+                    .collect(),
+                typ.clone(),
+            )),
+        };
+
+        let mut naive = requirements[0].iter().cloned().collect::<HashSet<_>>();
+        for columns in requirements.iter() {
+            naive.retain(|c| columns.contains(c));
+        }
+        assert_eq!(naive, columns(&[0]));
+
+        let mut actual = relation(rows.clone());
+        run(&mut actual);
+        let rows = rows
+            .into_iter()
+            .filter(|row| naive.iter().all(|c| row[*c] != Datum::Null))
+            .collect();
+        assert_eq!(actual, relation(rows));
+    }
+
+    /// Consolidated multiplicities of a union of constants and their negations.
+    fn multiplicities(relation: &RelationExpr) -> HashMap<repr::Row, isize> {
+        fn collect(relation: &RelationExpr, sign: isize, counts: &mut HashMap<repr::Row, isize>) {
+            match relation {
+                RelationExpr::Constant { rows, .. } => {
+                    for (row, diff) in rows {
+                        *counts.entry(row.clone()).or_insert(0) += sign * diff;
+                    }
+                }
+                RelationExpr::Negate { input } => collect(input, -sign, counts),
+                RelationExpr::Union { base, inputs } => {
+                    collect(base, sign, counts);
+                    for input in inputs {
+                        collect(input, sign, counts);
+                    }
+                }
+                RelationExpr::Filter { input, .. } => collect(input, sign, counts),
+                _ => panic!("unexpected relation: {:?}", relation),
+            }
+        }
+        let mut counts = HashMap::new();
+        collect(relation, 1, &mut counts);
+        counts.retain(|_, count| *count != 0);
+        counts
+    }
+
+    #[test]
+    fn union_with_negate_preserves_multiplicities() {
+        let constant =
+            |rows: Vec<(Vec<Datum>, isize)>| RelationExpr::constant_diff(rows, int64_typ(2));
+        let predicates = vec![is_not_null(ScalarExpr::column(1))];
+        let base = constant(vec![
+            (vec![Datum::Int64(1), Datum::Int64(1)], 2),
+            (vec![Datum::Int64(1), Datum::Null], 1),
+            (vec![Datum::Int64(2), Datum::Int64(2)], 1),
+        ]);
+        let negated = constant(vec![
+            (vec![Datum::Int64(1), Datum::Int64(1)], 1),
+            (vec![Datum::Int64(1), Datum::Null], 1),
+            (vec![Datum::Int64(2), Datum::Null], 1),
+        ]);
+        let mut relation = base.clone().union(negated.negate()).filter(predicates);
+        let mut expected = multiplicities(&relation);
+        expected.retain(|row, _| row.unpack()[1] != Datum::Null);
+        run(&mut relation);
+        assert_eq!(multiplicities(&relation), expected);
+        assert_eq!(
+            expected,
+            vec![
+                (repr::Row::pack(&[Datum::Int64(1), Datum::Int64(1)]), 1),
+                (repr::Row::pack(&[Datum::Int64(2), Datum::Int64(2)]), 1),
+            ]
+            .into_iter()
+            .collect::<HashMap<_, _>>()
+        );
+        // The threshold of the union is consistent with its input.
+        let mut thresholded = relation.clone().threshold();
+        run(&mut thresholded);
+        assert_eq!(thresholded, relation.threshold());
+    }
+
+    #[test]
+    fn reports_changes() {
+        let relation = |rows| {
+            RelationExpr::constant(rows, int64_typ(1))
+                .filter(vec![is_not_null(ScalarExpr::column(0))])
+        };
+        let changed = |mut relation| {
+            NonNullRequirements::default()
+                .transform_changed(
+                    &mut relation,
+                    TransformArgs::new(&mut Default::default(), &HashMap::new()),
+                )
+                .unwrap()
+        };
+        assert!(!changed(relation(vec![vec![Datum::Int64(1)]])));
+        assert!(changed(relation(vec![
+            vec![Datum::Int64(1)],
+            vec![Datum::Null]
+        ])));
+        assert!(changed(
+            get(1, int64_typ(1))
+                .map(vec![null_int64()])
+                .filter(vec![is_not_null(ScalarExpr::column(1))])
+        ));
+    }
+
+    #[test]
+    fn records_stats() {
+        let mut stats = TransformStats::default();
+        let mut transform = |mut relation: RelationExpr| {
+            let before = relation.clone();
+            NonNullRequirements::default()
+                .transform(
+                    &mut relation,
+                    TransformArgs {
+                        stats: Some(&mut stats),
+                        ..TransformArgs::new(&mut Default::default(), &HashMap::new())
+                    },
+                )
+                .unwrap();
+            assert_constant_rows_retained(&before, &relation);
+        };
+        transform(
+            RelationExpr::constant(
+                vec![
+                    vec![Datum::Int64(1), Datum::Null],
+                    vec![Datum::Null, Datum::Int64(1)],
+                    vec![Datum::Int64(1), Datum::Int64(1)],
+                    vec![Datum::Null, Datum::Null],
+                ],
+                int64_typ(2),
+            )
+            .filter(vec![is_not_null(ScalarExpr::column(0))]),
+        );
+        transform(
+            get(1, int64_typ(1))
+                .map(vec![null_int64()])
+                .filter(vec![is_not_null(ScalarExpr::column(1))]),
+        );
+        assert_eq!(
+            stats,
+            TransformStats {
+                constant_rows_pruned: 2,
+                branches_zeroed: 1,
+                constants_emptied: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn analyze_only() {
+        let id = LocalId::new(0);
+        let relation = || RelationExpr::Let {
+            id,
+            value: Box::new(get(1, int64_typ(2)).union(RelationExpr::constant(
+                vec![vec![Datum::Null, Datum::Int64(1)]],
+                int64_typ(2),
+            ))),
+            body: Box::new(
+                RelationExpr::Get {
+                    id: Id::Local(id),
+                    typ: int64_typ(2),
+                }
+                .map(vec![ScalarExpr::column(0)])
+                .filter(vec![is_not_null(ScalarExpr::column(2))]),
+            ),
+        };
+        let mut actual = relation();
+        let mut gets = HashMap::new();
+        NonNullRequirements::analyze_only()
+            .action(
+                &mut actual,
+                HashSet::new(),
+                &mut gets,
+                &mut None,
+                &mut TransformStats::default(),
+            )
+            .unwrap();
+        assert_eq!(actual, relation());
+        assert_eq!(gets.len(), 1);
+        assert_eq!(gets[&Id::Global(GlobalId::User(1))], vec![columns(&[0])]);
+    }
+
+    #[test]
+    fn malformed_project_errors() {
+        // The filter requires a column that the projection does not produce.
+        let mut relation = get(1, int64_typ(2))
+            .project(vec![0])
+            .filter(vec![is_not_null(ScalarExpr::column(1))]);
+        let result = NonNullRequirements::default().transform(
+            &mut relation,
+            TransformArgs::new(&mut Default::default(), &HashMap::new()),
+        );
+        match result {
+            Err(error @ TransformError::InvalidInput { .. }) => {
+                let message = error.to_string();
+                assert!(message.contains("NonNullRequirements"), "{}", message);
+                assert!(message.contains("column 1"), "{}", message);
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn scratch_is_cleared() {
+        let mut gets = HashMap::new();
+        gets.insert(Id::Global(GlobalId::User(2)), vec![columns(&[0])]);
+        let mut relation = get(1, int64_typ(1))
+            .union(get(2, int64_typ(1)))
+            .filter(vec![is_not_null(ScalarExpr::column(0))]);
+        NonNullRequirements::default()
+            .action_with_scratch(
+                &mut relation,
+                HashSet::new(),
+                &mut gets,
+                &mut None,
+                &mut TransformStats::default(),
+            )
+            .unwrap();
+        assert!(gets.is_empty());
+    }
+
+    #[test]
+    fn let_shadowing() {
+        let id = LocalId::new(0);
+        let local = || RelationExpr::Get {
+            id: Id::Local(id),
+            typ: int64_typ(2),
+        };
+        let constant = |rows| RelationExpr::constant(rows, int64_typ(2));
+        let requiring = |relation: RelationExpr, column| {
+            relation.filter(vec![is_not_null(ScalarExpr::column(column))])
+        };
+        let all_rows = || {
+            vec![
+                vec![Datum::Null, Datum::Int64(1)],
+                vec![Datum::Int64(1), Datum::Null],
+                vec![Datum::Int64(1), Datum::Int64(1)],
+            ]
+        };
+
+        // The requirements of the inner binding's references must not be
+        // attributed to the outer binding, which only requires `#0`.
+        let relation = |outer, inner| RelationExpr::Let {
+            id,
+            value: Box::new(constant(outer)),
+            body: Box::new(requiring(local(), 0).union(RelationExpr::Let {
+                id,
+                value: Box::new(constant(inner)),
+                body: Box::new(requiring(local(), 1)),
+            })),
+        };
+        let mut actual = relation(all_rows(), all_rows());
+        run(&mut actual);
+        let inner = all_rows()
+            .into_iter()
+            .filter(|row| row[1] != Datum::Null)
+            .collect();
+        assert_eq!(actual, relation(all_rows()[1..].to_vec(), inner));
+
+        // References to `id` in the inner binding's value are to the outer
+        // binding, which then carries the requirements of both.
+        let relation = |outer| RelationExpr::Let {
+            id,
+            value: Box::new(constant(outer)),
+            body: Box::new(RelationExpr::Let {
+                id,
+                value: Box::new(requiring(local(), 1)),
+                body: Box::new(requiring(local(), 0)),
+            }),
+        };
+        let mut actual = relation(all_rows());
+        run(&mut actual);
+        assert_eq!(actual, relation(vec![all_rows().remove(2)]));
+    }
+
+    #[test]
+    fn join_implementation_reset() {
+        let join = |rows| {
+            let mut join = RelationExpr::join_scalars(
+                vec![
+                    RelationExpr::constant(rows, int64_typ(1)),
+                    get(1, int64_typ(1)),
+                ],
+                vec![vec![ScalarExpr::column(0), ScalarExpr::column(1)]],
+            );
+            if let RelationExpr::Join { implementation, .. } = &mut join {
+                *implementation = JoinImplementation::Differential(
+                    (1, None),
+                    vec![(0, vec![ScalarExpr::column(0)])],
+                );
+            }
+            join.filter(vec![is_not_null(ScalarExpr::column(0))])
+        };
+        let implementation = |relation: &RelationExpr| match relation {
+            RelationExpr::Filter { input, .. } => match &**input {
+                RelationExpr::Join { implementation, .. } => implementation.clone(),
+                _ => panic!("expected a join"),
+            },
+            _ => panic!("expected a filter"),
+        };
+
+        // Without changes to its inputs the join keeps its implementation.
+        let mut relation = join(vec![vec![Datum::Int64(1)]]);
+        run(&mut relation);
+        assert_eq!(relation, join(vec![vec![Datum::Int64(1)]]));
+
+        let mut relation = join(vec![vec![Datum::Int64(1)], vec![Datum::Null]]);
+        run(&mut relation);
+        assert_eq!(implementation(&relation), JoinImplementation::Unimplemented);
+    }
+
+    #[test]
+    fn map_record_get_requires_record() {
+        let record = ScalarType::Record {
+            fields: vec![
+                ("a".into(), ScalarType::Int64),
+                ("b".into(), ScalarType::Int64),
+            ],
+        };
+        let relation = get(
+            1,
+            RelationType::new(vec![
+                ScalarType::Int64.nullable(true),
+                record.nullable(true),
+            ]),
+        )
+        .map(vec![
+            ScalarExpr::column(1).call_unary(UnaryFunc::RecordGet(1))
+        ])
+        .filter(vec![is_not_null(ScalarExpr::column(2))]);
+        let requirements = NonNullRequirements::default()
+            .analyze(&relation)
+            .unwrap()
+            .gets;
+        assert_eq!(
+            requirements[&Id::Global(GlobalId::User(1))],
+            vec![columns(&[1])]
+        );
+    }
+
+    #[test]
+    fn recursion_limit() {
+        let deep = |depth: usize| {
+            let mut relation = get(1, int64_typ(1));
+            for _ in 0..depth {
+                relation = relation.map(vec![ScalarExpr::column(0)]);
+            }
+            relation
+        };
+        // Frames in debug builds are large enough that reaching the default
+        // limit may itself overflow the small stack of a test thread.
+        std::thread::Builder::new()
+            .stack_size(64 << 20)
+            .spawn(move || {
+                let relation = deep(5000);
+                let result = NonNullRequirements::default()
+                    .analyze(&relation)
+                    .unwrap()
+                    .requirements();
+                match result {
+                    Err(TransformError::Internal(message)) => {
+                        assert_eq!(message, "recursion limit exceeded")
+                    }
+                    result => panic!("unexpected result {:?}", result),
+                }
+
+                let relation = deep(100);
+                let limited = NonNullRequirements::default().with_recursion_limit(99);
+                assert!(limited.analyze(&relation).is_err());
+                let limited = limited.with_recursion_limit(100);
+                assert!(limited.analyze(&relation).is_ok());
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn analyze() {
+        // Both references to the source require `#0`, but only one `#1`.
+        let source = || get(1, int64_typ(3));
+        let relation = source()
+            .filter(vec![
+                is_not_null(ScalarExpr::column(0)),
+                is_not_null(ScalarExpr::column(1)),
+            ])
+            .union(source().filter(vec![is_not_null(
+                ScalarExpr::column(0).call_binary(ScalarExpr::column(2), BinaryFunc::AddInt64),
+            )]))
+            .union(RelationExpr::constant(
+                vec![vec![Datum::Null, Datum::Int64(1), Datum::Int64(2)]],
+                int64_typ(3),
+            ))
+            .filter(vec![is_not_null(ScalarExpr::column(0))]);
+        let original = relation.clone();
+        let requirements = NonNullRequirements::default()
+            .analyze(&relation)
+            .unwrap()
+            .requirements();
+        assert_eq!(relation, original);
+        assert_eq!(
+            requirements,
+            vec![(Id::Global(GlobalId::User(1)), columns(&[0]))]
+                .into_iter()
+                .collect::<HashMap<_, _>>()
+        );
+
+        // Local ids are not sources, and are not reported.
+        let id = LocalId::new(0);
+        let relation = RelationExpr::Let {
+            id,
+            value: Box::new(source()),
+            body: Box::new(
+                RelationExpr::Get {
+                    id: Id::Local(id),
+                    typ: int64_typ(3),
+                }
+                .filter(vec![is_not_null(ScalarExpr::column(1))]),
+            ),
+        };
+        assert_eq!(
+            NonNullRequirements::default()
+                .analyze(&relation)
+                .unwrap()
+                .requirements(),
+            vec![(Id::Global(GlobalId::User(1)), columns(&[1]))]
+                .into_iter()
+                .collect::<HashMap<_, _>>()
+        );
+    }
+
+    #[test]
+    fn zeroed_map_keeps_type() {
+        let typ = RelationType::new(vec![
+            ScalarType::Int64.nullable(false),
+            ScalarType::String.nullable(true),
+        ])
+        .with_key(vec![0]);
+        let mut relation = get(1, typ)
+            .map(vec![null_int64()])
+            .filter(vec![is_not_null(ScalarExpr::column(2))]);
+        let before = relation.typ();
+        run(&mut relation);
+        let input = match &relation {
+            RelationExpr::Filter { input, .. } => input,
+            _ => panic!("expected a filter, found {:?}", relation),
+        };
+        assert!(matches!(**input, RelationExpr::Constant { ref rows, .. } if rows.is_empty()));
+        let after = relation.typ();
+        assert_eq!(after.column_types, before.column_types);
+        assert!(before.keys.iter().all(|key| after.keys.contains(key)));
+    }
+
+    #[test]
+    fn zeroed_reduce_input_clears_expected_group_size() {
+        let reduce = |input: RelationExpr, expected_group_size| {
+            input
+                .reduce(
+                    vec![1],
+                    vec![AggregateExpr {
+                        func: AggregateFunc::Count,
+                        expr: ScalarExpr::Column(0),
+                        distinct: false,
+                    }],
+                    expected_group_size,
+                )
+                .filter(vec![is_not_null(ScalarExpr::column(0))])
+        };
+        let input = || get(1, int64_typ(1)).map(vec![null_int64()]);
+        let mut relation = reduce(input(), Some(16));
+        run(&mut relation);
+        assert_eq!(relation, reduce(empty(int64_typ(2)), None));
+
+        // An input that satisfies the requirements keeps its hint.
+        let input = || get(1, int64_typ(1)).map(vec![ScalarExpr::column(0)]);
+        let mut relation = reduce(input(), Some(16));
+        run(&mut relation);
+        assert_eq!(relation, reduce(input(), Some(16)));
+    }
+
+    #[test]
+    fn out_of_range_columns_error() {
+        let mut relation = get(1, int64_typ(2)).map(vec![ScalarExpr::column(0)]);
+        let result = NonNullRequirements::default().action(
+            &mut relation,
+            columns(&[1, 3]),
+            &mut HashMap::new(),
+            &mut None,
+            &mut TransformStats::default(),
+        );
+        match result {
+            Err(TransformError::InvalidInput { transform, detail }) => {
+                assert_eq!(transform, "NonNullRequirements");
+                assert_eq!(detail, "non-null requirement on column 3 of Map of arity 3");
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+    }
+
+    #[test]
+    fn wide_constant_pruning() {
+        // Each row has a null in a different column of a wide constant.
+        let arity = 64;
+        let rows = (0..arity)
+            .map(|i| {
+                (0..arity)
+                    .map(|c| {
+                        if c == i {
+                            Datum::Null
+                        } else {
+                            Datum::Int64(c as i64)
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        for required in vec![vec![], vec![0], vec![5, 17], vec![arity - 1]] {
+            let mut relation = RelationExpr::constant(rows.clone(), int64_typ(arity));
+            NonNullRequirements::default()
+                .action(
+                    &mut relation,
+                    columns(&required),
+                    &mut HashMap::new(),
+                    &mut None,
+                    &mut TransformStats::default(),
+                )
+                .unwrap();
+            let expected = rows
+                .iter()
+                .filter(|row| required.iter().all(|c| !row[*c].is_null()))
+                .cloned()
+                .collect();
+            assert_constant_rows_retained(
+                &RelationExpr::constant(rows.clone(), int64_typ(arity)),
+                &relation,
+            );
+            assert_eq!(relation, RelationExpr::constant(expected, int64_typ(arity)));
+        }
+    }
+
+    #[test]
+    fn transform_with_required() {
+        let relation = || {
+            RelationExpr::constant(
+                vec![
+                    vec![Datum::Int64(1), Datum::Null],
+                    vec![Datum::Int64(2), Datum::Int64(3)],
+                ],
+                int64_typ(2),
+            )
+        };
+        let transform = |relation: &mut RelationExpr, required| {
+            NonNullRequirements::default().transform_with_required(
+                relation,
+                required,
+                TransformArgs::new(&mut Default::default(), &HashMap::new()),
+            )
+        };
+
+        let mut actual = relation();
+        transform(&mut actual, columns(&[])).unwrap();
+        assert_eq!(actual, relation());
+
+        let mut actual = relation();
+        transform(&mut actual, columns(&[1])).unwrap();
+        assert_eq!(
+            actual,
+            RelationExpr::constant(vec![vec![Datum::Int64(2), Datum::Int64(3)]], int64_typ(2))
+        );
+
+        let mut actual = relation();
+        assert!(matches!(
+            transform(&mut actual, columns(&[2])),
+            Err(TransformError::InvalidInput { .. })
+        ));
+        assert_eq!(actual, relation());
+    }
+
+    #[test]
+    fn monotonic_global_reduce_over_pruned_input() {
+        // A reduction without keys has no output for an empty input, unlike
+        // SQL's global aggregates, which are planned to add their default row
+        // separately. Pruning each input row then leaves no `min` behind, as
+        // the filter would have discarded its null.
+        let reduce = |rows| {
+            let mut reduce = RelationExpr::constant(rows, int64_typ(2)).reduce(
+                vec![],
+                vec![AggregateExpr {
+                    func: AggregateFunc::MinInt64,
+                    expr: ScalarExpr::column(1),
+                    distinct: false,
+                }],
+                None,
+            );
+            if let RelationExpr::Reduce { monotonic, .. } = &mut reduce {
+                *monotonic = true;
+            }
+            reduce.filter(vec![is_not_null(ScalarExpr::column(0))])
+        };
+        let mut relation = reduce(vec![
+            vec![Datum::Int64(1), Datum::Null],
+            vec![Datum::Int64(2), Datum::Null],
+        ]);
+        run(&mut relation);
+        assert_eq!(relation, reduce(vec![]));
+    }
+
+    thread_local! {
+        /// Messages logged on this thread by `CaptureLogger`.
+        static CAPTURED: std::cell::RefCell<Vec<String>> = std::cell::RefCell::new(Vec::new());
+    }
+
+    /// A logger that captures messages in `CAPTURED`.
+    struct CaptureLogger;
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            CAPTURED.with(|captured| captured.borrow_mut().push(record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static CAPTURE_LOGGER: CaptureLogger = CaptureLogger;
+
+    #[test]
+    fn traces_zeroed_map() {
+        let _ = log::set_logger(&CAPTURE_LOGGER);
+        log::set_max_level(log::LevelFilter::Warn);
+        let transform = |trace| {
+            let mut relation = get(1, int64_typ(2))
+                .map(vec![ScalarExpr::column(0), null_int64()])
+                .filter(vec![is_not_null(ScalarExpr::column(3))]);
+            NonNullRequirements::default()
+                .transform(
+                    &mut relation,
+                    TransformArgs {
+                        trace,
+                        ..TransformArgs::new(&mut Default::default(), &HashMap::new())
+                    },
+                )
+                .unwrap();
+            CAPTURED.with(|captured| captured.replace(Vec::new()))
+        };
+        assert_eq!(transform(false), Vec::<String>::new());
+        assert_eq!(
+            transform(true),
+            vec![
+                "NonNullRequirements zeroed out a Map: column 3 must be non-null, \
+                 but scalar 1 is a null Int64"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn non_null_fixpoint_needs_two_rounds() {
+        // Folding reduces the `if` to a literal null only after
+        // `NonNullRequirements` has run, and the next round zeroes the map.
+        // Predicates are not pushed through the `TopK`, which would let
+        // folding discard the rows itself.
+        let relation = || {
+            get(1, int64_typ(1))
+                .map(vec![ScalarExpr::literal_ok(
+                    Datum::True,
+                    ScalarType::Bool.nullable(false),
+                )
+                .if_then_else(null_int64(), ScalarExpr::column(0))])
+                .top_k(vec![], vec![], None, 0)
+                .filter(vec![is_not_null(ScalarExpr::column(1))])
+        };
+        let expected = empty(int64_typ(2))
+            .top_k(vec![], vec![], None, 0)
+            .filter(vec![is_not_null(ScalarExpr::column(1))]);
+
+        let mut once = relation();
+        let transforms: Vec<Box<dyn Transform>> = vec![
+            Box::new(NonNullRequirements::default()),
+            Box::new(crate::predicate_pushdown::PredicatePushdown),
+            Box::new(crate::reduction::FoldConstants),
+        ];
+        for transform in transforms {
+            transform
+                .transform(
+                    &mut once,
+                    TransformArgs::new(&mut Default::default(), &HashMap::new()),
+                )
+                .unwrap();
+        }
+        assert_ne!(once, expected);
+
+        let mut relation = relation();
+        non_null_fixpoint(
+            &mut relation,
+            TransformArgs::new(&mut Default::default(), &HashMap::new()),
+        )
+        .unwrap();
+        assert_eq!(relation, expected);
+    }
+
+    #[test]
+    fn idempotent() {
+        let null_row = || RelationExpr::constant(vec![vec![Datum::Null]], int64_typ(1));
+        let outer_join = RelationExpr::join_scalars(
+            vec![
+                get(1, non_null_int64_typ(1)),
+                get(2, int64_typ(1)).union(null_row()),
+            ],
+            vec![vec![ScalarExpr::column(0), ScalarExpr::column(1)]],
+        );
+        let (x, y) = (LocalId::new(0), LocalId::new(1));
+        let local = |id| RelationExpr::Get {
+            id: Id::Local(id),
+            typ: int64_typ(1),
+        };
+        let nested_lets = RelationExpr::Let {
+            id: x,
+            value: Box::new(null_row().union(get(1, int64_typ(1)))),
+            body: Box::new(RelationExpr::Let {
+                id: y,
+                value: Box::new(local(x).filter(vec![is_not_null(ScalarExpr::column(0))])),
+                body: Box::new(local(y).union(local(x))),
+            }),
+        }
+        .filter(vec![is_not_null(ScalarExpr::column(0))]);
+        let union = get(1, int64_typ(1))
+            .union(get(2, RelationType::new(vec![])).map(vec![null_int64()]))
+            .union(null_row())
+            .filter(vec![is_not_null(ScalarExpr::column(0))]);
+        let reduce = RelationExpr::constant(
+            vec![
+                vec![Datum::Int64(1), Datum::Null],
+                vec![Datum::Int64(2), Datum::Int64(3)],
+            ],
+            int64_typ(2),
+        )
+        .reduce(
+            vec![0],
+            vec![AggregateExpr {
+                func: AggregateFunc::MinInt64,
+                expr: ScalarExpr::column(1),
+                distinct: false,
+            }],
+            Some(4),
+        )
+        .filter(vec![is_not_null(ScalarExpr::column(1))]);
+
+        let transform = |relation: &mut RelationExpr| {
+            NonNullRequirements::default()
+                .transform_changed(
+                    relation,
+                    TransformArgs::new(&mut Default::default(), &HashMap::new()),
+                )
+                .unwrap()
+        };
+        for mut relation in vec![outer_join, nested_lets, union, reduce] {
+            assert!(transform(&mut relation), "{}", relation.pretty());
+            let once = relation.clone();
+            assert!(!transform(&mut relation), "{}", once.pretty());
+            assert_eq!(relation, once);
+        }
+    }
+
+    #[test]
+    fn union_with_pruned_base() {
+        let base = |rows| RelationExpr::constant(rows, int64_typ(1));
+        let union = |base: RelationExpr, inputs| {
+            RelationExpr::Union {
+                base: Box::new(base),
+                inputs,
+            }
+            .filter(vec![is_not_null(ScalarExpr::column(0))])
+        };
+        let inputs = || {
+            vec![
+                get(1, int64_typ(1)),
+                RelationExpr::constant(vec![vec![Datum::Int64(1)]], int64_typ(1)),
+            ]
+        };
+
+        // Pruning each row of the base leaves the inputs as they were.
+        let mut relation = union(base(vec![vec![Datum::Null], vec![Datum::Null]]), inputs());
+        run(&mut relation);
+        assert_eq!(relation, union(base(vec![]), inputs()));
+
+        // A union of its base alone is pruned like the base.
+        let mut relation = union(base(vec![vec![Datum::Null], vec![Datum::Int64(1)]]), vec![]);
+        run(&mut relation);
+        assert_eq!(relation, union(base(vec![vec![Datum::Int64(1)]]), vec![]));
+    }
+
+    #[test]
+    fn eliminable_branches() {
+        let null_row = || RelationExpr::constant(vec![vec![Datum::Null]], int64_typ(1));
+        let null_map = || get(2, RelationType::new(vec![])).map(vec![null_int64()]);
+        let count = |branches: Vec<RelationExpr>| {
+            let relation = RelationExpr::union_many(branches, int64_typ(1))
+                .filter(vec![is_not_null(ScalarExpr::column(0))]);
+            let original = relation.clone();
+            let count = NonNullRequirements::default()
+                .analyze(&relation)
+                .unwrap()
+                .eliminable_branches();
+            assert_eq!(relation, original);
+            count
+        };
+        assert_eq!(count(vec![get(1, int64_typ(1))]), 0);
+        assert_eq!(count(vec![get(1, int64_typ(1)), null_map()]), 1);
+        assert_eq!(
+            count(vec![
+                get(1, int64_typ(1)),
+                null_map(),
+                null_row(),
+                null_map()
+            ]),
+            3
+        );
+        // A constant with rows that remain is not eliminated.
+        let rows = vec![vec![Datum::Null], vec![Datum::Int64(1)]];
+        let constant = RelationExpr::constant(rows, int64_typ(1));
+        assert_eq!(count(vec![get(1, int64_typ(1)), constant]), 0);
+    }
+
+    #[test]
+    fn requirements_cache() {
+        // A large expression shared by each of many predicates.
+        let shared = (1..100).fold(ScalarExpr::column(0), |expr, c| {
+            expr.call_binary(ScalarExpr::column(c), BinaryFunc::AddInt64)
+        });
+        let predicates = (0..100)
+            .map(|c| {
+                is_not_null(shared.clone()).call_binary(
+                    ScalarExpr::column(c).call_binary(ScalarExpr::column(c), BinaryFunc::Eq),
+                    BinaryFunc::And,
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut columns = HashSet::new();
+        let mut cache = RequirementsCache::default();
+        for predicate in predicates.iter() {
+            predicate_non_null_requirements(predicate, &mut columns, &mut cache);
+        }
+        assert_eq!(columns, (0..100).collect::<HashSet<_>>());
+        // The requirements of `!isnull(shared)` and `shared` are each derived
+        // once, rather than once per predicate, along with those of each
+        // distinct equality.
+        assert_eq!(cache.misses, 2 + 100);
+    }
+
+    #[test]
+    fn pruned_flat_map_input_clears_demand() {
+        let flat_map = |rows, demand| RelationExpr::FlatMap {
+            input: Box::new(RelationExpr::constant(rows, int64_typ(2))),
+            func: TableFunc::GenerateSeriesInt64,
+            exprs: vec![ScalarExpr::column(0), ScalarExpr::column(1)],
+            demand,
+        };
+        let rows = vec![
+            vec![Datum::Int64(1), Datum::Int64(2)],
+            vec![Datum::Int64(1), Datum::Null],
+        ];
+        let mut relation = flat_map(rows.clone(), Some(vec![2]));
+        run(&mut relation);
+        assert_eq!(relation, flat_map(rows[..1].to_vec(), None));
+
+        // An input left as it was keeps its demand.
+        let mut relation = flat_map(rows[..1].to_vec(), Some(vec![2]));
+        run(&mut relation);
+        assert_eq!(relation, flat_map(rows[..1].to_vec(), Some(vec![2])));
+    }
+
+    #[test]
+    fn wide_join() {
+        // Ten inputs equated in a chain, of which only the first is non-null.
+        // Its constraint reaches the padding of each of the others.
+        let padded = |id, padding| get(id, int64_typ(1)).union(padding);
+        let padding = || RelationExpr::constant(vec![vec![Datum::Null]], int64_typ(1));
+        let join = |padding: &dyn Fn() -> RelationExpr| {
+            let inputs = std::iter::once(get(0, non_null_int64_typ(1)))
+                .chain((1..10).map(|id| padded(id, padding())))
+                .collect();
+            let equivalences = vec![(0..10).map(ScalarExpr::column).collect()];
+            RelationExpr::join_scalars(inputs, equivalences)
+        };
+        let mut relation = join(&padding);
+        run(&mut relation);
+        assert_eq!(relation, join(&|| empty(int64_typ(1))));
+    }
+
+    #[test]
+    fn without_constant_pruning() {
+        let rows = || vec![vec![Datum::Null], vec![Datum::Int64(1)]];
+        let relation = |padding| {
+            RelationExpr::constant(rows(), int64_typ(1))
+                .union(padding)
+                .filter(vec![is_not_null(ScalarExpr::column(0))])
+        };
+        let padding = get(1, RelationType::new(vec![])).map(vec![null_int64()]);
+        let mut actual = relation(padding.clone());
+        NonNullRequirements::default()
+            .with_prune_constants(false)
+            .action(
+                &mut actual,
+                HashSet::new(),
+                &mut HashMap::new(),
+                &mut None,
+                &mut TransformStats::default(),
+            )
+            .unwrap();
+        assert_constant_rows_retained(&relation(padding.clone()), &actual);
+        assert_eq!(actual, relation(empty(padding.typ())));
+    }
+
+    #[test]
+    fn map_filter_join() {
+        // The map's required `#3` requires `#2`, which the filter requires as
+        // well, and which the join equates with `#0` of the left input. The
+        // padding on that input is then pruned of its null row.
+        let plan = |padding: Vec<Vec<Datum<'static>>>| {
+            let left = get(1, int64_typ(1)).union(RelationExpr::constant(padding, int64_typ(1)));
+            RelationExpr::join_scalars(
+                vec![left, get(2, int64_typ(2))],
+                vec![vec![ScalarExpr::column(0), ScalarExpr::column(2)]],
+            )
+            .filter(vec![ScalarExpr::column(2).call_binary(
+                ScalarExpr::literal_ok(Datum::Int64(5), ScalarType::Int64.nullable(false)),
+                BinaryFunc::Gt,
+            )])
+            .map(vec![ScalarExpr::column(2).call_unary(UnaryFunc::NegInt64)])
+        };
+        let mut stats = TransformStats::default();
+        let mut relation = plan(vec![vec![Datum::Null], vec![Datum::Int64(7)]]);
+        NonNullRequirements::default()
+            .transform_with_required(
+                &mut relation,
+                columns(&[3]),
+                TransformArgs {
+                    stats: Some(&mut stats),
+                    ..TransformArgs::new(&mut Default::default(), &HashMap::new())
+                },
+            )
+            .unwrap();
+        assert_constant_rows_retained(
+            &plan(vec![vec![Datum::Null], vec![Datum::Int64(7)]]),
+            &relation,
+        );
+        assert_eq!(relation, plan(vec![vec![Datum::Int64(7)]]));
+        assert_eq!(stats.constant_rows_pruned, 1);
+        assert_eq!(stats.branches_zeroed, 0);
+    }
+
+    #[test]
+    fn source_requirement_report() {
+        // `#2` is required directly, and `#0` because it is equated with it.
+        let join = RelationExpr::join_scalars(
+            vec![get(1, int64_typ(2)), get(2, int64_typ(2))],
+            vec![vec![ScalarExpr::Column(0), ScalarExpr::Column(2)]],
+        );
+        let mut report = SourceRequirementReport::default();
+        let transform = |relation: &mut RelationExpr, report: &mut SourceRequirementReport| {
+            NonNullRequirements::default()
+                .transform(
+                    relation,
+                    TransformArgs {
+                        source_requirements: Some(report),
+                        ..TransformArgs::new(&mut Default::default(), &HashMap::new())
+                    },
+                )
+                .unwrap()
+        };
+        transform(
+            &mut join.clone().filter(vec![
+                is_not_null(ScalarExpr::Column(2)),
+                is_not_null(ScalarExpr::Column(3)),
+            ]),
+            &mut report,
+        );
+        let expected = vec![
+            (GlobalId::User(1), columns(&[0])),
+            (GlobalId::User(2), columns(&[0, 1])),
+        ];
+        assert_eq!(report.sources, expected.into_iter().collect());
+
+        // Another relation keeps only the columns both require.
+        transform(
+            &mut join.filter(vec![is_not_null(ScalarExpr::Column(3))]),
+            &mut report,
+        );
+        let expected = vec![
+            (GlobalId::User(1), columns(&[])),
+            (GlobalId::User(2), columns(&[1])),
+        ];
+        assert_eq!(report.sources, expected.into_iter().collect());
+    }
+
+    #[test]
+    fn arrangement_key_nullability_report() {
+        // The filter proves `#0` non-null, but not `#1`, and so of the keys
+        // only the first is non-null.
+        let keys = vec![
+            vec![ScalarExpr::column(0)],
+            vec![ScalarExpr::column(0), ScalarExpr::column(1)],
+        ];
+        let mut relation = get(1, int64_typ(2))
+            .filter(vec![is_not_null(ScalarExpr::column(0))])
+            .arrange_by(&keys);
+        let mut report = SourceRequirementReport::default();
+        NonNullRequirements::default()
+            .transform(
+                &mut relation,
+                TransformArgs {
+                    source_requirements: Some(&mut report),
+                    ..TransformArgs::new(&mut Default::default(), &HashMap::new())
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            report.arrangements,
+            vec![ArrangementNullability {
+                keys,
+                non_null: vec![true, false],
+            }]
+        );
+    }
+
+    #[test]
+    fn join_is_deterministic() {
+        // Requirements are sets, whose iteration order depends on the order
+        // in which columns are inserted. The plan that results should not
+        // depend on the order of the equivalence or of the predicates that
+        // insert them.
+        let join = |input, equivalence: &[usize], predicates: &[usize]| {
+            RelationExpr::join_scalars(
+                vec![input, get(1, int64_typ(2))],
+                vec![equivalence.iter().map(|c| ScalarExpr::column(*c)).collect()],
+            )
+            .filter(
+                predicates
+                    .iter()
+                    .map(|c| is_not_null(ScalarExpr::column(*c)))
+                    .collect(),
+            )
+        };
+        let input = || {
+            int64_constant(
+                3,
+                vec![
+                    vec![Some(1), Some(2), Some(3)],
+                    vec![None, Some(2), Some(3)],
+                    vec![Some(4), None, Some(6)],
+                    vec![Some(7), Some(8), Some(9)],
+                    vec![Some(10), Some(11), None],
+                ],
+            )
+        };
+        let pruned = || {
+            int64_constant(
+                3,
+                vec![
+                    vec![Some(1), Some(2), Some(3)],
+                    vec![None, Some(2), Some(3)],
+                    vec![Some(7), Some(8), Some(9)],
+                ],
+            )
+        };
+        let orders = vec![
+            vec![1, 2, 4],
+            vec![1, 4, 2],
+            vec![2, 1, 4],
+            vec![2, 4, 1],
+            vec![4, 1, 2],
+            vec![4, 2, 1],
+        ];
+        for equivalence in &[[0, 3], [3, 0]] {
+            for predicates in &orders {
+                let mut actual = join(input(), &equivalence[..], &predicates[..]);
+                run(&mut actual);
+                assert_eq!(actual, join(pruned(), &equivalence[..], &predicates[..]));
+            }
+        }
+    }
+
+    #[test]
+    fn literal_filter_predicates() {
+        let literal = |datum| ScalarExpr::literal_ok(datum, ScalarType::Bool.nullable(true));
+        let never_true = vec![
+            literal(Datum::False),
+            ScalarExpr::literal_null(ScalarType::Bool.nullable(true)),
+        ];
+        for predicate in never_true {
+            let relation =
+                get(1, int64_typ(2)).filter(vec![is_not_null(ScalarExpr::column(0)), predicate]);
+            let mut actual = relation.clone();
+            let mut stats = TransformStats::default();
+            NonNullRequirements::default()
+                .transform(
+                    &mut actual,
+                    TransformArgs {
+                        stats: Some(&mut stats),
+                        ..TransformArgs::new(&mut Default::default(), &HashMap::new())
+                    },
+                )
+                .unwrap();
+            assert_eq!(actual, empty(int64_typ(2)));
+            assert_eq!(stats.branches_zeroed, 1);
+
+            // Only recording requirements leaves the filter in place.
+            let mut actual = relation.clone();
+            NonNullRequirements::analyze_only()
+                .action(
+                    &mut actual,
+                    HashSet::new(),
+                    &mut HashMap::new(),
+                    &mut None,
+                    &mut TransformStats::default(),
+                )
+                .unwrap();
+            assert_eq!(actual, relation);
+        }
+
+        // A literal true requires nothing, and is left for other transforms
+        // to remove.
+        let relation = get(1, int64_typ(2)).filter(vec![literal(Datum::True)]);
+        let mut actual = relation.clone();
+        let mut gets = HashMap::new();
+        NonNullRequirements::default()
+            .action(
+                &mut actual,
+                HashSet::new(),
+                &mut gets,
+                &mut None,
+                &mut TransformStats::default(),
+            )
+            .unwrap();
+        assert_eq!(actual, relation);
+        assert_eq!(gets[&Id::Global(GlobalId::User(1))], vec![columns(&[])]);
+    }
+
+    #[test]
+    fn action_at_path() {
+        let constant = |rows| RelationExpr::constant(rows, int64_typ(2));
+        let relation = |rows| {
+            RelationExpr::join_scalars(
+                vec![get(1, int64_typ(2)), constant(rows)],
+                vec![vec![ScalarExpr::column(0), ScalarExpr::column(2)]],
+            )
+            .filter(vec![is_not_null(ScalarExpr::column(1))])
+        };
+        let rows = || {
+            vec![
+                vec![Datum::Null, Datum::Int64(1)],
+                vec![Datum::Int64(2), Datum::Null],
+            ]
+        };
+
+        // The right input of the join, beneath the filter, requires `#1`.
+        let mut actual = relation(rows());
+        let transform = NonNullRequirements::default();
+        assert!(transform
+            .action_at_path(&mut actual, &[0, 1], columns(&[1]))
+            .unwrap());
+        assert_eq!(actual, relation(vec![vec![Datum::Null, Datum::Int64(1)]]));
+
+        for path in vec![vec![0, 2], vec![0, 1, 0]] {
+            let mut actual = relation(rows());
+            match transform.action_at_path(&mut actual, &path, columns(&[1])) {
+                Err(TransformError::InvalidInput { .. }) => {}
+                result => panic!("unexpected result {:?}", result),
+            }
+            assert_eq!(actual, relation(rows()));
+        }
+    }
+
+    #[test]
+    fn constructed_composite_filter() {
+        // As in `WHERE row(#0, #1) IS NOT NULL`, which holds for each row.
+        let funcs = vec![
+            VariadicFunc::RecordCreate {
+                field_names: vec!["a".into(), "b".into()],
+            },
+            VariadicFunc::ListCreate {
+                elem_type: ScalarType::Int64,
+            },
+        ];
+        for func in funcs {
+            let composite = ScalarExpr::CallVariadic {
+                func,
+                exprs: vec![ScalarExpr::column(0), ScalarExpr::column(1)],
+            };
+            let relation = get(1, int64_typ(2)).filter(vec![is_not_null(composite)]);
+            let requirements = NonNullRequirements::default()
+                .analyze(&relation)
+                .unwrap()
+                .gets;
+            assert_eq!(
+                requirements[&Id::Global(GlobalId::User(1))],
+                vec![columns(&[])]
+            );
+        }
+    }
+
+    #[test]
+    fn over_reported_requirements_error() {
+        // Scalars that report requirements on columns their input lacks,
+        // e.g. `#5` of an input of arity 2, are caught at the input, or where
+        // their types are derived if they need typing.
+        let plans = vec![
+            (
+                get(1, int64_typ(2))
+                    .map(vec![ScalarExpr::column(5)])
+                    .filter(vec![is_not_null(ScalarExpr::column(2))]),
+                "Map references column 5 of an input of arity 2",
+            ),
+            (
+                get(1, int64_typ(2))
+                    .filter(vec![is_not_null(ScalarExpr::column(5))])
+                    .project(vec![0]),
+                "non-null requirement on column 5 of Get of arity 2",
+            ),
+        ];
+        for (relation, expected) in plans {
+            match NonNullRequirements::default().analyze(&relation) {
+                Err(TransformError::InvalidInput { transform, detail }) => {
+                    assert_eq!(transform, "NonNullRequirements");
+                    assert_eq!(detail, expected);
+                }
+                result => panic!("unexpected result {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn short_project_outputs_error() {
+        // A projection with fewer outputs than a required column index, as a
+        // faulty transform might produce, errors rather than panics, as the
+        // requirements are checked on entry.
+        let mut relation = get(1, int64_typ(2)).project(vec![1]);
+        let result = NonNullRequirements::default().action(
+            &mut relation,
+            columns(&[0, 3]),
+            &mut HashMap::new(),
+            &mut None,
+            &mut TransformStats::default(),
+        );
+        match result {
+            Err(TransformError::InvalidInput { transform, detail }) => {
+                assert_eq!(transform, "NonNullRequirements");
+                assert_eq!(
+                    detail,
+                    "non-null requirement on column 3 of Project of arity 1"
+                );
+            }
+            result => panic!("unexpected result {:?}", result),
+        }
+        assert_eq!(relation, get(1, int64_typ(2)).project(vec![1]));
+    }
+
+    #[test]
+    fn arrangement_of_zeroed_input() {
+        // The input of the arrangement is zeroed out, and its keys remain
+        // valid for the empty collection that replaces it.
+        let keys = vec![vec![ScalarExpr::column(0)], vec![ScalarExpr::column(1)]];
+        let predicates = vec![is_not_null(ScalarExpr::column(1))];
+        let mut relation = get(1, int64_typ(1))
+            .map(vec![null_int64()])
+            .arrange_by(&keys)
+            .filter(predicates.clone());
+        run(&mut relation);
+        assert_eq!(
+            relation,
+            empty(int64_typ(2)).arrange_by(&keys).filter(predicates)
+        );
+    }
+
+    #[test]
+    fn explain_changes() {
+        // The constant input of the join, at `[0, 1]`, loses the rows in which
+        // `#2` is null.
+        let relation = RelationExpr::join_scalars(
+            vec![
+                get(1, int64_typ(2)),
+                int64_constant(1, vec![vec![None], vec![Some(1)], vec![None]]),
+            ],
+            vec![vec![ScalarExpr::column(0), ScalarExpr::column(2)]],
+        )
+        .filter(vec![is_not_null(ScalarExpr::column(2))]);
+        let mut after = relation.clone();
+        run(&mut after);
+        assert_eq!(
+            super::explain_changes(&relation, &after),
+            vec!["pruned 2 rows from constant at path [0, 1]".to_string()]
+        );
+
+        // The branch at `[0, 1]` introduces nulls in a required column.
+        let relation = get(1, int64_typ(2))
+            .union(get(1, int64_typ(1)).map(vec![null_int64()]))
+            .filter(vec![is_not_null(ScalarExpr::column(1))]);
+        let mut after = relation.clone();
+        run(&mut after);
+        assert_eq!(
+            super::explain_changes(&relation, &after),
+            vec!["zeroed subtree at path [0, 1]".to_string()]
+        );
+
+        assert!(super::explain_changes(&relation, &relation).is_empty());
+    }
+
+    #[test]
+    fn assume_all_non_strict() {
+        let conservative = NonNullRequirements::default().with_assume_all_non_strict(true);
+
+        // Scalar expressions impose no requirements, even on columns they
+        // reference directly.
+        let relation = get(1, int64_typ(2)).filter(vec![
+            is_not_null(ScalarExpr::column(0)),
+            ScalarExpr::column(1).call_binary(ScalarExpr::column(0), BinaryFunc::Gt),
+        ]);
+        let id = Id::Global(GlobalId::User(1));
+        assert_eq!(
+            NonNullRequirements::default()
+                .analyze(&relation)
+                .unwrap()
+                .requirements()[&id],
+            columns(&[0, 1])
+        );
+        assert_eq!(
+            conservative.analyze(&relation).unwrap().requirements()[&id],
+            columns(&[])
+        );
+        assert_eq!(
+            conservative
+                .analyze(&relation)
+                .unwrap()
+                .eliminable_branches(),
+            0
+        );
+
+        // A literal null equated with a column that is non-null by type, or
+        // a literally null predicate, still eliminates its branch.
+        let join = RelationExpr::join_scalars(
+            vec![
+                get(1, int64_typ(1)).map(vec![null_int64()]),
+                get(2, non_null_int64_typ(1)),
+            ],
+            vec![vec![ScalarExpr::column(1), ScalarExpr::column(2)]],
+        );
+        assert_eq!(
+            conservative.analyze(&join).unwrap().eliminable_branches(),
+            1
+        );
+        let filter = get(1, int64_typ(1)).filter(vec![ScalarExpr::literal_null(
+            ScalarType::Bool.nullable(true),
+        )]);
+        assert_eq!(
+            conservative.analyze(&filter).unwrap().eliminable_branches(),
+            1
+        );
+    }
+
+    #[test]
+    fn requires_column_non_null_of_filter() {
+        // The filter forces `#0` non-null, but says nothing of `#1`.
+        let relation = get(1, int64_typ(2)).filter(vec![is_not_null(ScalarExpr::column(0))]);
+        assert!(requires_column_non_null(&relation, 0));
+        assert!(!requires_column_non_null(&relation, 1));
+        assert!(!requires_column_non_null(&relation, 2));
+
+        // Without the filter, only the types are consulted.
+        assert!(!requires_column_non_null(&get(1, int64_typ(2)), 0));
+        assert!(requires_column_non_null(&get(1, non_null_int64_typ(2)), 0));
+    }
+
+    #[test]
+    fn analyze_eliminable_paths() {
+        // A left outer join of sources 1 and 2, whose rows of source 1 padded
+        // with nulls, at `[0, 1]`, cannot pass the filter. For brevity the
+        // padded branch pads every row of source 1, rather than only those
+        // without a match.
+        let inner = RelationExpr::join_scalars(
+            vec![get(1, int64_typ(2)), get(2, int64_typ(2))],
+            vec![vec![ScalarExpr::column(0), ScalarExpr::column(2)]],
+        );
+        let outer = get(1, int64_typ(2)).map(vec![null_int64(), null_int64()]);
+        let relation = inner.union(outer).filter(vec![
+            is_not_null(ScalarExpr::column(0)),
+            is_not_null(ScalarExpr::column(3)),
+        ]);
+        let analysis = NonNullRequirements::default().analyze(&relation).unwrap();
+        // The filter requires `#0`, and so the equivalent `#2`, as well as
+        // `#3`, of which `#2` and `#3` are columns of source 2.
+        let expected = vec![
+            (Id::Global(GlobalId::User(1)), columns(&[0])),
+            (Id::Global(GlobalId::User(2)), columns(&[0, 1])),
+        ]
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+        assert_eq!(analysis.requirements(), expected);
+        assert_eq!(analysis.eliminable_paths, vec![vec![0, 1]]);
+        assert_eq!(analysis.eliminable_branches(), 1);
+        // The reference to source 1 within the eliminated branch contributes
+        // no requirements.
+        assert_eq!(
+            analysis.gets[&Id::Global(GlobalId::User(1))],
+            vec![columns(&[0])]
+        );
+    }
+
+    #[test]
+    fn left_outer_join_padding() {
+        // A left outer join of sources 1 and 2 on `#0 = #2`, as it is planned:
+        // the inner join, bound to a local identifier, and the rows of source
+        // 1 without a match, padded with nulls for the columns of source 2.
+        let id = LocalId::new(0);
+        let inner = || RelationExpr::Get {
+            id: Id::Local(id),
+            typ: int64_typ(4),
+        };
+        let outer_join = |padded| RelationExpr::Let {
+            id,
+            value: Box::new(RelationExpr::join_scalars(
+                vec![get(1, int64_typ(2)), get(2, int64_typ(2))],
+                vec![vec![ScalarExpr::column(0), ScalarExpr::column(2)]],
+            )),
+            body: Box::new(inner().union(padded)),
+        };
+        let padded = get(1, int64_typ(2))
+            .union(inner().project(vec![0, 1]).distinct().negate())
+            .map(vec![null_int64(), null_int64()]);
+
+        // Requiring `#3`, of source 2, eliminates the padded branch.
+        let filter =
+            |relation: RelationExpr| relation.filter(vec![is_not_null(ScalarExpr::column(3))]);
+        let mut relation = filter(outer_join(padded.clone()));
+        let analysis = NonNullRequirements::default().analyze(&relation).unwrap();
+        assert_eq!(analysis.eliminable_paths, vec![vec![0, 1, 1]]);
+        assert_eq!(
+            analysis.requirements()[&Id::Global(GlobalId::User(2))],
+            columns(&[1])
+        );
+        run(&mut relation);
+        assert_eq!(relation, filter(outer_join(empty(int64_typ(4)))));
+
+        // Requiring `#1`, of source 1, leaves the padded branch in place.
+        let filter =
+            |relation: RelationExpr| relation.filter(vec![is_not_null(ScalarExpr::column(1))]);
+        let mut relation = filter(outer_join(padded.clone()));
+        run(&mut relation);
+        assert_eq!(relation, filter(outer_join(padded)));
+    }
+
+    #[test]
+    fn reanalyze_after_editing_join_input() {
+        // The filter on `#2` makes the first two inputs non-null on the
+        // columns they equate, until it is removed.
+        let plan = |filtered: bool| {
+            let mut second = get(2, int64_typ(2));
+            if filtered {
+                second = second.filter(vec![is_not_null(ScalarExpr::column(0))]);
+            }
+            RelationExpr::join(
+                vec![get(1, int64_typ(2)), second, get(3, int64_typ(1))],
+                vec![vec![(0, 0), (1, 0)]],
+            )
+            .filter(vec![is_not_null(ScalarExpr::column(4))])
+        };
+        let analysis = NonNullRequirements::default();
+        let previous = analysis.analyze_incremental(&plan(true)).unwrap();
+        assert_eq!(previous.reused, 0);
+        assert!(analysis.analyze(&plan(true)).unwrap().recorded.is_empty());
+
+        // Only the edited input and its ancestors are analyzed anew, and the
+        // first input as well, as it is now visited without requirements.
+        let relation = plan(false);
+        let incremental = analysis.reanalyze(&relation, &[0, 1], previous).unwrap();
+        assert_eq!(incremental.gets, analysis.analyze(&relation).unwrap().gets);
+        assert_eq!(
+            incremental.gets[&Id::Global(GlobalId::User(1))],
+            vec![columns(&[])]
+        );
+        assert_eq!(incremental.reused, 1);
+    }
+
+    #[test]
+    fn reanalyze_after_editing_let_value() {
+        // The filter in the value makes the second input of the join in the
+        // body non-null on the column it equates, until it is removed.
+        let id = LocalId::new(0);
+        let plan = |filtered: bool| {
+            let mut value = get(1, int64_typ(2));
+            if filtered {
+                value = value.filter(vec![is_not_null(ScalarExpr::column(0))]);
+            }
+            let body = RelationExpr::join(
+                vec![
+                    RelationExpr::Get {
+                        id: Id::Local(id),
+                        typ: int64_typ(2),
+                    },
+                    get(2, int64_typ(1)),
+                ],
+                vec![vec![(0, 0), (1, 0)]],
+            );
+            RelationExpr::Let {
+                id,
+                value: Box::new(value),
+                body: Box::new(body),
+            }
+        };
+        let analysis = NonNullRequirements::default();
+        let previous = analysis.analyze_incremental(&plan(true)).unwrap();
+        assert_eq!(
+            previous.gets[&Id::Global(GlobalId::User(2))],
+            vec![columns(&[0])]
+        );
+
+        let relation = plan(false);
+        let incremental = analysis.reanalyze(&relation, &[0], previous).unwrap();
+        assert_eq!(incremental.gets, analysis.analyze(&relation).unwrap().gets);
+        assert_eq!(
+            incremental.gets[&Id::Global(GlobalId::User(2))],
+            vec![columns(&[])]
+        );
+    }
+
+    #[test]
+    fn action_returning() {
+        // The required `#0` reaches source 1, but the required `#2`, a literal
+        // null, zeroes out the second input of the join instead.
+        let mut relation = RelationExpr::join(
+            vec![
+                get(1, int64_typ(1)),
+                get(2, int64_typ(1)).map(vec![null_int64()]),
+            ],
+            vec![],
+        );
+        let mut gets = HashMap::new();
+        let escaped = NonNullRequirements::default()
+            .action_returning(&mut relation, columns(&[0, 2]), &mut gets)
+            .unwrap();
+        assert_eq!(escaped, columns(&[0]));
+        assert_eq!(
+            gets,
+            vec![(Id::Global(GlobalId::User(1)), vec![columns(&[0])])]
+                .into_iter()
+                .collect::<HashMap<_, _>>()
+        );
+        assert_eq!(
+            relation,
+            RelationExpr::join(vec![get(1, int64_typ(1)), empty(int64_typ(2))], vec![])
+        );
+
+        // Only requirements beyond those of the plan itself reach a `Get`.
+        let mut relation = get(1, int64_typ(2)).filter(vec![is_not_null(ScalarExpr::column(0))]);
+        let escaped = NonNullRequirements::default()
+            .action_returning(&mut relation, columns(&[0, 1]), &mut HashMap::new())
+            .unwrap();
+        assert_eq!(escaped, columns(&[1]));
+
+        // The requirements at a binding are those of the plan only if the
+        // plan imposes them at every reference.
+        let id = LocalId::new(0);
+        let local = || {
+            RelationExpr::Get {
+                id: Id::Local(id),
+                typ: int64_typ(2),
             }
+            .filter(vec![is_not_null(ScalarExpr::column(0))])
+        };
+        let relation = |last: RelationExpr| RelationExpr::Let {
+            id,
+            value: Box::new(get(1, int64_typ(2))),
+            body: Box::new(local().union(last)),
+        };
+        for (last, expected) in vec![(local(), columns(&[1])), (local().negate(), columns(&[1]))] {
+            let escaped = NonNullRequirements::default()
+                .action_returning(&mut relation(last), columns(&[0, 1]), &mut HashMap::new())
+                .unwrap();
+            assert_eq!(escaped, expected);
         }
+        let mut relation = relation(RelationExpr::Get {
+            id: Id::Local(id),
+            typ: int64_typ(2),
+        });
+        let escaped = NonNullRequirements::default()
+            .action_returning(&mut relation, columns(&[0, 1]), &mut HashMap::new())
+            .unwrap();
+        assert_eq!(escaped, columns(&[0, 1]));
     }
 }
@@ -14,7 +14,7 @@
 
 // TODO(frank): evaluate for redundancy with `column_knowledge`, or vice-versa.
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 use expr::{AggregateExpr, AggregateFunc, RelationExpr, ScalarExpr, UnaryFunc};
 use repr::{Datum, RelationType, ScalarType};
 
@@ -26,24 +26,35 @@ impl crate::Transform for NonNullable {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut_pre(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl NonNullable {
-    /// Harvests information about non-nullability of columns from sources.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Harvests information about non-nullability of columns from sources,
+    /// reporting whether it changed `relation`.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
+        let mut changed = false;
         match relation {
             RelationExpr::Map { input, scalars } => {
                 if scalars.iter().any(|s| scalar_contains_isnull(s)) {
                     let mut metadata = input.typ();
                     for scalar in scalars.iter_mut() {
-                        scalar_nonnullable(scalar, &metadata);
+                        changed |= scalar_nonnullable(scalar, &metadata);
                         let typ = scalar.typ(&metadata);
                         metadata.column_types.push(typ);
                     }
@@ -53,7 +64,7 @@ impl NonNullable {
                 if predicates.iter().any(|s| scalar_contains_isnull(s)) {
                     let metadata = input.typ();
                     for predicate in predicates.iter_mut() {
-                        scalar_nonnullable(predicate, &metadata);
+                        changed |= scalar_nonnullable(predicate, &metadata);
                     }
                 }
             }
@@ -69,13 +80,14 @@ impl NonNullable {
                 }) {
                     let metadata = input.typ();
                     for aggregate in aggregates.iter_mut() {
-                        scalar_nonnullable(&mut aggregate.expr, &metadata);
-                        aggregate_nonnullable(aggregate, &metadata);
+                        changed |= scalar_nonnullable(&mut aggregate.expr, &metadata);
+                        changed |= aggregate_nonnullable(aggregate, &metadata);
                     }
                 }
             }
             _ => {}
         }
+        changed
     }
 }
 
@@ -95,7 +107,8 @@ fn scalar_contains_isnull(expr: &ScalarExpr) -> bool {
 }
 
 /// Transformations to scalar functions, based on nonnullability of columns.
-fn scalar_nonnullable(expr: &mut ScalarExpr, metadata: &RelationType) {
+fn scalar_nonnullable(expr: &mut ScalarExpr, metadata: &RelationType) -> bool {
+    let mut changed = false;
     // Tests for null can be replaced by "false" for non-nullable columns.
     expr.visit_mut(&mut |e| {
         if let ScalarExpr::CallUnary {
@@ -106,19 +119,23 @@ fn scalar_nonnullable(expr: &mut ScalarExpr, metadata: &RelationType) {
             if let ScalarExpr::Column(c) = &**expr {
                 if !metadata.column_types[*c].nullable {
                     *e = ScalarExpr::literal_ok(Datum::False, ScalarType::Bool.nullable(false));
+                    changed = true;
                 }
             }
         }
-    })
+    });
+    changed
 }
 
 /// Transformations to aggregation functions, based on nonnullability of columns.
-fn aggregate_nonnullable(expr: &mut AggregateExpr, metadata: &RelationType) {
+fn aggregate_nonnullable(expr: &mut AggregateExpr, metadata: &RelationType) -> bool {
     // An aggregate that is a count of non-nullable data can be replaced by
     // count(true).
     if let (AggregateFunc::Count, ScalarExpr::Column(c)) = (&expr.func, &expr.expr) {
         if !metadata.column_types[*c].nullable && !expr.distinct {
             expr.expr = ScalarExpr::literal_ok(Datum::True, ScalarType::Bool.nullable(false));
+            return true;
         }
     }
+    false
 }
@@ -0,0 +1,271 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Elides tests for null in predicates over columns proven non-null.
+//!
+//! Where `NonNullable` relies on the nullability that types report, this
+//! transformation uses the columns that `non_null_columns` proves non-null,
+//! which include those established by filters and join equivalences below.
+//! Only facts established within the filtered plan are used, and a filter is
+//! never used to justify eliding its own predicates. The columns proven
+//! non-null are derived once for the whole plan, rather than for each filter.
+//!
+//! The transformation is not part of the default `Optimizer`, and must be
+//! added to a pipeline explicitly.
+
+use crate::nonnull_requirements::TypeTree;
+use crate::{Transform, TransformArgs};
+use expr::{RelationExpr, ScalarExpr, UnaryFunc};
+use repr::{Datum, ScalarType};
+
+/// Elides tests for null in predicates over columns proven non-null.
+#[derive(Debug)]
+pub struct NullFilterElision;
+
+impl crate::Transform for NullFilterElision {
+    fn transform(
+        &self,
+        relation: &mut RelationExpr,
+        args: TransformArgs,
+    ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        // A malformed plan, whose types cannot be derived, is left as is.
+        match TypeTree::derive(relation, true, usize::MAX) {
+            Ok(known) => Ok(self.elide(relation, &known)),
+            Err(_) => Ok(false),
+        }
+    }
+}
+
+impl NullFilterElision {
+    /// Elides tests for null in predicates over columns proven non-null,
+    /// reporting whether it changed `relation`.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
+        if let RelationExpr::Filter { input, .. } = relation {
+            if let Ok(known) = TypeTree::derive(input, true, usize::MAX) {
+                return self.elide_filter(relation, &known).0;
+            }
+        }
+        false
+    }
+
+    /// Elides tests for null in the filters of `relation`, whose column types
+    /// and those of its subexpressions are those of `known`, reporting
+    /// whether it changed `relation`.
+    ///
+    /// Filters are visited before their inputs, and eliding tests in one
+    /// leaves the types of its input as they were.
+    fn elide(&self, relation: &mut RelationExpr, known: &TypeTree) -> bool {
+        if let RelationExpr::Filter { .. } = relation {
+            match self.elide_filter(relation, &known.inputs[0]) {
+                (_, true) => {
+                    // The filter was replaced by its input.
+                    self.elide(relation, &known.inputs[0]);
+                    return true;
+                }
+                (true, false) => {
+                    self.elide_inputs(relation, known);
+                    return true;
+                }
+                (false, false) => {}
+            }
+        }
+        self.elide_inputs(relation, known)
+    }
+
+    /// Elides tests for null in the filters of the inputs of `relation`,
+    /// whose types are those of the inputs of `known`.
+    fn elide_inputs(&self, relation: &mut RelationExpr, known: &TypeTree) -> bool {
+        let mut changed = false;
+        let mut inputs = known.inputs.iter();
+        relation.visit1_mut(|input| {
+            if let Some(known) = inputs.next() {
+                changed |= self.elide(input, known);
+            }
+        });
+        changed
+    }
+
+    /// Elides tests for null in the predicates of the filter `relation`, over
+    /// the columns that `known`, the types of its input, reports non-nullable.
+    ///
+    /// Returns whether it changed the filter, and whether no predicates
+    /// remain and the filter was replaced by its input.
+    fn elide_filter(&self, relation: &mut RelationExpr, known: &TypeTree) -> (bool, bool) {
+        if let RelationExpr::Filter { input, predicates } = relation {
+            if !predicates.iter().any(contains_column_isnull) {
+                return (false, false);
+            }
+            let mut changed = false;
+            let columns = known.non_null_columns();
+            for predicate in predicates.iter_mut() {
+                let mut elided = false;
+                predicate.visit_mut(&mut |e| {
+                    if let ScalarExpr::CallUnary {
+                        func: UnaryFunc::IsNull,
+                        expr,
+                    } = e
+                    {
+                        if let ScalarExpr::Column(c) = &**expr {
+                            if columns.contains(c) {
+                                *e = ScalarExpr::literal_ok(
+                                    Datum::False,
+                                    ScalarType::Bool.nullable(false),
+                                );
+                                elided = true;
+                            }
+                        }
+                    }
+                });
+                if elided {
+                    predicate.reduce(&known.typ);
+                    changed = true;
+                }
+            }
+            let len = predicates.len();
+            predicates.retain(|p| !p.is_literal_true());
+            changed |= predicates.len() != len;
+            if predicates
+                .iter()
+                .any(|p| p.is_literal_false() || p.is_literal_null())
+            {
+                relation.take_safely();
+                return (true, false);
+            } else if predicates.is_empty() {
+                *relation = input.take_dangerous();
+                return (true, true);
+            }
+            return (changed, false);
+        }
+        (false, false)
+    }
+}
+
+/// True if the expression tests a column for null.
+fn contains_column_isnull(expr: &ScalarExpr) -> bool {
+    let mut result = false;
+    expr.visit(&mut |e| {
+        if let ScalarExpr::CallUnary {
+            func: UnaryFunc::IsNull,
+            expr,
+        } = e
+        {
+            if let ScalarExpr::Column(_) = &**expr {
+                result = true;
+            }
+        }
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use expr::{BinaryFunc, GlobalId, Id, RelationExpr, ScalarExpr, UnaryFunc};
+    use repr::{RelationType, ScalarType};
+
+    use super::NullFilterElision;
+    use crate::{Transform, TransformArgs};
+
+    fn get(typ: RelationType) -> RelationExpr {
+        RelationExpr::Get {
+            id: Id::Global(GlobalId::User(1)),
+            typ,
+        }
+    }
+
+    fn typ() -> RelationType {
+        RelationType::new(vec![
+            ScalarType::Int64.nullable(false),
+            ScalarType::Int64.nullable(true),
+            ScalarType::Bool.nullable(true),
+        ])
+    }
+
+    fn is_null(column: usize) -> ScalarExpr {
+        ScalarExpr::column(column).call_unary(UnaryFunc::IsNull)
+    }
+
+    #[test]
+    fn is_null_over_non_null_column() {
+        // `#0` is non-null by type, and `#1` by the filter below.
+        for column in 0..2 {
+            let input = get(typ()).filter(vec![is_null(1).call_unary(UnaryFunc::Not)]);
+            let mut relation = input.clone().filter(vec![is_null(column)]);
+            NullFilterElision.action(&mut relation);
+            assert_eq!(relation, RelationExpr::constant(vec![], input.typ()));
+        }
+    }
+
+    #[test]
+    fn is_not_null_over_non_null_column() {
+        let mut relation = get(typ()).filter(vec![is_null(0).call_unary(UnaryFunc::Not)]);
+        NullFilterElision.action(&mut relation);
+        assert_eq!(relation, get(typ()));
+
+        // Only the test for null is simplified, and other conjuncts remain.
+        let mut relation = get(typ()).filter(vec![
+            is_null(0)
+                .call_unary(UnaryFunc::Not)
+                .call_binary(ScalarExpr::column(2), BinaryFunc::And),
+            is_null(0).call_unary(UnaryFunc::Not),
+            is_null(1),
+        ]);
+        NullFilterElision.action(&mut relation);
+        assert_eq!(
+            relation,
+            get(typ()).filter(vec![ScalarExpr::column(2), is_null(1)])
+        );
+    }
+
+    #[test]
+    fn is_not_null_over_join_equivalence() {
+        // Joins equate null keys, so `#4` is non-null only when it is
+        // equated with `#0`, which is non-null by type, and not with `#1`.
+        for (left, right, elided) in vec![(0, 4, true), (1, 4, false)] {
+            let join = RelationExpr::join_scalars(
+                vec![get(typ()), get(typ())],
+                vec![vec![ScalarExpr::column(left), ScalarExpr::column(right)]],
+            );
+            let predicates = vec![is_null(right).call_unary(UnaryFunc::Not)];
+            let mut relation = join.clone().filter(predicates.clone());
+            assert_eq!(NullFilterElision.action(&mut relation), elided);
+            if elided {
+                assert_eq!(relation, join);
+            } else {
+                assert_eq!(relation, join.filter(predicates));
+            }
+        }
+    }
+
+    #[test]
+    fn stacked_filters() {
+        // Each filter is elided by the one beneath it, and the last remains.
+        let input = get(typ()).filter(vec![is_null(1).call_unary(UnaryFunc::Not)]);
+        let mut relation = input
+            .clone()
+            .filter(vec![is_null(1).call_unary(UnaryFunc::Not)])
+            .filter(vec![is_null(0).call_unary(UnaryFunc::Not)]);
+        NullFilterElision
+            .transform(
+                &mut relation,
+                TransformArgs::new(&mut Default::default(), &HashMap::new()),
+            )
+            .unwrap();
+        assert_eq!(relation, input);
+    }
+}
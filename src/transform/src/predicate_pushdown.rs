@@ -46,15 +46,15 @@
 //!    ]);
 //!
 //! use transform::{Transform, TransformArgs};
-//! PredicatePushdown.transform(&mut expr, TransformArgs {
-//!   id_gen: &mut Default::default(),
-//!   indexes: &std::collections::HashMap::new(),
-//! });
+//! PredicatePushdown.transform(
+//!     &mut expr,
+//!     TransformArgs::new(&mut Default::default(), &std::collections::HashMap::new()),
+//! );
 //! ```
 
 use std::collections::{HashMap, HashSet};
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 use expr::{AggregateFunc, Id, RelationExpr, ScalarExpr};
 use repr::{Datum, ScalarType};
 
@@ -66,11 +66,18 @@ impl crate::Transform for PredicatePushdown {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
         let mut empty = HashMap::new();
-        self.action(relation, &mut empty);
-        Ok(())
+        Ok(self.action(relation, &mut empty))
     }
 }
 
@@ -133,11 +140,14 @@ impl PredicatePushdown {
     /// applied to each `Get` expression, so that the predicate can
     /// then be pushed through to a `Let` binding, or to the external
     /// source of the data if the `Get` binds to another view.
+    ///
+    /// Returns whether it changed `relation`.
     fn action(
         &self,
         relation: &mut RelationExpr,
         get_predicates: &mut HashMap<Id, HashSet<ScalarExpr>>,
-    ) {
+    ) -> bool {
+        let mut changed = false;
         // In the case of Filter or Get we have specific work to do;
         // otherwise we should recursively descend.
         match relation {
@@ -146,13 +156,14 @@ impl PredicatePushdown {
                 // logic to apply to consider pushing `predicates` down.
                 match &mut **input {
                     RelationExpr::Let { id, value, body } => {
+                        changed = true;
                         // Push all predicates to the body.
                         **body = body
                             .take_dangerous()
                             .filter(std::mem::replace(predicates, Vec::new()));
 
                         // Push predicates and collect intersection at `Get`s.
-                        self.action(body, get_predicates);
+                        changed |= self.action(body, get_predicates);
 
                         // `get_predicates` should now contain the intersection
                         // of predicates at each *use* of the binding. If it is
@@ -164,7 +175,7 @@ impl PredicatePushdown {
                         }
 
                         // Continue recursively on the value.
-                        self.action(value, get_predicates);
+                        changed |= self.action(value, get_predicates);
                     }
                     RelationExpr::Get { id, .. } => {
                         // We can report the predicates upward in `get_predicates`,
@@ -237,6 +248,7 @@ impl PredicatePushdown {
                                     );
                                     equivalences.push(vec![(**expr1).clone(), (**expr2).clone()]);
                                     pushed = true;
+                                    changed = true;
                                 }
                             }
 
@@ -247,6 +259,7 @@ impl PredicatePushdown {
 
                         // Push down equality constraints supported by the same single input.
                         for equivalence in equivalences.iter_mut() {
+                            changed |= equivalence.windows(2).any(|pair| pair[0] >= pair[1]);
                             equivalence.sort();
                             equivalence.dedup(); // <-- not obviously necessary.
 
@@ -271,12 +284,14 @@ impl PredicatePushdown {
                                         },
                                     );
                                     equivalence.remove(pos);
+                                    changed = true;
                                 } else {
                                     pos += 1;
                                 }
                             }
                         }
 
+                        changed |= push_downs.iter().any(|push_down| !push_down.is_empty());
                         let new_inputs = inputs
                             .drain(..)
                             .zip(push_downs)
@@ -294,11 +309,12 @@ impl PredicatePushdown {
 
                         // Recursively descend on each of the inputs.
                         for input in inputs.iter_mut() {
-                            self.action(input, get_predicates);
+                            changed |= self.action(input, get_predicates);
                         }
 
                         if retain.is_empty() {
                             *relation = (**input).clone();
+                            changed = true;
                         } else {
                             *predicates = retain;
                         }
@@ -349,16 +365,19 @@ impl PredicatePushdown {
 
                         if !push_down.is_empty() {
                             *inner = Box::new(inner.take_dangerous().filter(push_down));
+                            changed = true;
                         }
-                        self.action(inner, get_predicates);
+                        changed |= self.action(inner, get_predicates);
 
                         if !retain.is_empty() {
                             *predicates = retain;
                         } else {
                             *relation = input.take_dangerous();
+                            changed = true;
                         }
                     }
                     RelationExpr::Project { input, outputs } => {
+                        changed = true;
                         let predicates = predicates.drain(..).map(|mut predicate| {
                             predicate.visit_mut(&mut |e| {
                                 if let ScalarExpr::Column(i) = e {
@@ -372,19 +391,20 @@ impl PredicatePushdown {
                             .filter(predicates)
                             .project(outputs.clone());
 
-                        self.action(relation, get_predicates);
+                        changed |= self.action(relation, get_predicates);
                     }
                     RelationExpr::Filter {
                         input,
                         predicates: predicates2,
                     } => {
+                        changed = true;
                         *relation = input.take_dangerous().filter(
                             predicates
                                 .clone()
                                 .into_iter()
                                 .chain(predicates2.clone().into_iter()),
                         );
-                        self.action(relation, get_predicates);
+                        changed |= self.action(relation, get_predicates);
                     }
                     RelationExpr::Map { input, scalars } => {
                         // In the case of a Filter { Map {...} }, we can always push down the Filter
@@ -424,12 +444,15 @@ impl PredicatePushdown {
                                 retained.push(predicate);
                             }
                         }
+                        // The filter is rebuilt as it was unless some predicates are pushed
+                        // down, or none remain.
+                        changed |= !pushdown.is_empty() || retained.is_empty();
                         let scalars = std::mem::replace(scalars, Vec::new());
                         let mut result = input.take_dangerous();
                         if !pushdown.is_empty() {
                             result = result.filter(pushdown);
                         }
-                        self.action(&mut result, get_predicates);
+                        changed |= self.action(&mut result, get_predicates);
                         result = result.map(scalars);
                         if !retained.is_empty() {
                             result = result.filter(retained);
@@ -437,19 +460,21 @@ impl PredicatePushdown {
                         *relation = result;
                     }
                     RelationExpr::Union { base, inputs } => {
+                        changed = true;
                         *base = Box::new(base.take_dangerous().filter(predicates.clone()));
                         for input in inputs {
                             *input = input.take_dangerous().filter(predicates.clone());
-                            self.action(input, get_predicates);
+                            changed |= self.action(input, get_predicates);
                         }
                     }
                     RelationExpr::Negate { input: inner } => {
+                        changed = true;
                         let predicates = std::mem::replace(predicates, Vec::new());
                         *relation = inner.take_dangerous().filter(predicates).negate();
-                        self.action(relation, get_predicates);
+                        changed |= self.action(relation, get_predicates);
                     }
                     x => {
-                        x.visit1_mut(|e| self.action(e, get_predicates));
+                        x.visit1_mut(|e| changed |= self.action(e, get_predicates));
                     }
                 }
             }
@@ -462,9 +487,10 @@ impl PredicatePushdown {
             }
             x => {
                 // Recursively descend.
-                x.visit1_mut(|e| self.action(e, get_predicates));
+                x.visit1_mut(|e| changed |= self.action(e, get_predicates));
             }
         }
+        changed
     }
 
     /// Defines a criteria for inlining scalar expressions.
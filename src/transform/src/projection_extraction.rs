@@ -9,7 +9,7 @@
 
 //! Transform column references in a `Map` into a `Project`.
 
-use crate::{RelationExpr, ScalarExpr, TransformArgs};
+use crate::{RelationExpr, ScalarExpr, Transform, TransformArgs};
 
 /// Transform column references in a `Map` into a `Project`, or repeated
 /// aggregations in a `Reduce` into a `Project`.
@@ -20,18 +20,28 @@ impl crate::Transform for ProjectionExtraction {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl ProjectionExtraction {
-    /// Transform column references in a `Map` into a `Project`.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Transform column references in a `Map` into a `Project`, reporting
+    /// whether it did.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
         if let RelationExpr::Map { input, scalars } = relation {
             if scalars.iter().any(|s| matches!(s, ScalarExpr::Column(_))) {
                 let input_arity = input.arity();
@@ -54,6 +64,7 @@ impl ProjectionExtraction {
                         scalar.permute(&outputs);
                     }
                     *relation = relation.take_dangerous().project(outputs);
+                    return true;
                 }
             }
         } else if let RelationExpr::Reduce {
@@ -98,7 +109,9 @@ impl ProjectionExtraction {
             }
             if projection.iter().enumerate().any(|(i, p)| i != *p) {
                 *relation = relation.take_dangerous().project(projection);
+                return true;
             }
         }
+        false
     }
 }
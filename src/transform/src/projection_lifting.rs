@@ -14,7 +14,7 @@
 use std::collections::HashMap;
 use std::mem;
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 use expr::{Id, RelationExpr};
 
 /// Hoist projections through operators.
@@ -25,25 +25,34 @@ impl crate::Transform for ProjectionLifting {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
-        self.action(relation, &mut HashMap::new());
-        Ok(())
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        Ok(self.action(relation, &mut HashMap::new()))
     }
 }
 
 impl ProjectionLifting {
-    /// Hoist projections through operators.
+    /// Hoist projections through operators, reporting whether it hoisted any.
     pub fn action(
         &self,
         relation: &mut RelationExpr,
         // Map from names to new get type and projection required at use.
         gets: &mut HashMap<Id, (repr::RelationType, Vec<usize>)>,
-    ) {
+    ) -> bool {
+        let mut changed = false;
         match relation {
             RelationExpr::Constant { .. } => {}
             RelationExpr::Get { id, .. } => {
                 if let Some((typ, columns)) = gets.get(id) {
+                    changed = true;
                     *relation = RelationExpr::Get {
                         id: *id,
                         typ: typ.clone(),
@@ -52,25 +61,27 @@ impl ProjectionLifting {
                 }
             }
             RelationExpr::Let { id, value, body } => {
-                self.action(value, gets);
+                changed |= self.action(value, gets);
                 let id = Id::Local(*id);
                 if let RelationExpr::Project { input, outputs } = &mut **value {
                     let typ = input.typ();
                     let prior = gets.insert(id, (typ, outputs.clone()));
                     assert!(!prior.is_some());
                     **value = input.take_dangerous();
+                    changed = true;
                 }
 
-                self.action(body, gets);
+                changed |= self.action(body, gets);
                 gets.remove(&id);
             }
             RelationExpr::Project { input, outputs } => {
-                self.action(input, gets);
+                changed |= self.action(input, gets);
                 if let RelationExpr::Project {
                     input: inner,
                     outputs: inner_outputs,
                 } = &mut **input
                 {
+                    changed = true;
                     for output in outputs.iter_mut() {
                         *output = inner_outputs[*output];
                     }
@@ -78,12 +89,13 @@ impl ProjectionLifting {
                 }
             }
             RelationExpr::Map { input, scalars } => {
-                self.action(input, gets);
+                changed |= self.action(input, gets);
                 if let RelationExpr::Project {
                     input: inner,
                     outputs,
                 } = &mut **input
                 {
+                    changed = true;
                     // Retain projected columns and scalar columns.
                     let mut new_outputs = outputs.clone();
                     let inner_arity = inner.arity();
@@ -106,12 +118,13 @@ impl ProjectionLifting {
                 exprs,
                 demand,
             } => {
-                self.action(input, gets);
+                changed |= self.action(input, gets);
                 if let RelationExpr::Project {
                     input: inner,
                     outputs,
                 } = &mut **input
                 {
+                    changed = true;
                     // TODO: Preserve demand.
                     *demand = None;
                     // Retain projected columns and scalar columns.
@@ -131,12 +144,13 @@ impl ProjectionLifting {
                 }
             }
             RelationExpr::Filter { input, predicates } => {
-                self.action(input, gets);
+                changed |= self.action(input, gets);
                 if let RelationExpr::Project {
                     input: inner,
                     outputs,
                 } = &mut **input
                 {
+                    changed = true;
                     // Rewrite scalar expressions using inner columns.
                     for predicate in predicates.iter_mut() {
                         predicate.permute(outputs);
@@ -154,7 +168,7 @@ impl ProjectionLifting {
                 implementation,
             } => {
                 for input in inputs.iter_mut() {
-                    self.action(input, gets);
+                    changed |= self.action(input, gets);
                 }
 
                 // Track the location of the projected columns in the un-projected join.
@@ -168,6 +182,7 @@ impl ProjectionLifting {
                         }
                         temp_arity += input.arity();
                         *join_input = input.take_dangerous();
+                        changed = true;
                     } else {
                         let arity = join_input.arity();
                         projection.extend(temp_arity..(temp_arity + arity));
@@ -201,12 +216,13 @@ impl ProjectionLifting {
                 expected_group_size: _,
             } => {
                 // Reduce *absorbs* projections, which is amazing!
-                self.action(input, gets);
+                changed |= self.action(input, gets);
                 if let RelationExpr::Project {
                     input: inner,
                     outputs,
                 } = &mut **input
                 {
+                    changed = true;
                     for key in group_key.iter_mut() {
                         key.permute(outputs);
                     }
@@ -224,12 +240,13 @@ impl ProjectionLifting {
                 offset,
                 monotonic: _,
             } => {
-                self.action(input, gets);
+                changed |= self.action(input, gets);
                 if let RelationExpr::Project {
                     input: inner,
                     outputs,
                 } = &mut **input
                 {
+                    changed = true;
                     for key in group_key.iter_mut() {
                         *key = outputs[*key];
                     }
@@ -248,12 +265,13 @@ impl ProjectionLifting {
                 }
             }
             RelationExpr::Negate { input } => {
-                self.action(input, gets);
+                changed |= self.action(input, gets);
                 if let RelationExpr::Project {
                     input: inner,
                     outputs,
                 } = &mut **input
                 {
+                    changed = true;
                     *relation = inner.take_dangerous().negate().project(outputs.clone());
                 }
             }
@@ -262,13 +280,13 @@ impl ProjectionLifting {
                 // If we could reason that the input cannot be negative, we
                 // would be able to lift the projection, but otherwise our
                 // action on weights need to accumulate the restricted rows.
-                self.action(input, gets);
+                changed |= self.action(input, gets);
             }
             RelationExpr::Union { base, inputs } => {
                 // We cannot, in general, lift projections out of unions.
-                self.action(base, gets);
+                changed |= self.action(base, gets);
                 for input in &mut *inputs {
-                    self.action(input, gets);
+                    changed |= self.action(input, gets);
                 }
 
                 if let RelationExpr::Project {
@@ -302,16 +320,18 @@ impl ProjectionLifting {
                             }
                         }
                         *relation = relation.take_dangerous().project(base_outputs);
+                        changed = true;
                     }
                 }
             }
             RelationExpr::ArrangeBy { input, keys } => {
-                self.action(input, gets);
+                changed |= self.action(input, gets);
                 if let RelationExpr::Project {
                     input: inner,
                     outputs,
                 } = &mut **input
                 {
+                    changed = true;
                     for key_set in keys.iter_mut() {
                         for key in key_set.iter_mut() {
                             key.permute(outputs);
@@ -324,5 +344,6 @@ impl ProjectionLifting {
                 }
             }
         }
+        changed
     }
 }
@@ -13,7 +13,7 @@
 //! set of columns that form unique keys for the input, the reduce
 //! can be simplified to a map operation.
 
-use crate::{RelationExpr, ScalarExpr, TransformArgs};
+use crate::{RelationExpr, ScalarExpr, Transform, TransformArgs};
 
 /// Removes `Reduce` when the input has as unique keys the keys of the reduce.
 #[derive(Debug)]
@@ -23,18 +23,28 @@ impl crate::Transform for ReduceElision {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl ReduceElision {
-    /// Removes `Reduce` when the input has as unique keys the keys of the reduce.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Removes `Reduce` when the input has as unique keys the keys of the reduce,
+    /// reporting whether it did.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
         if let RelationExpr::Reduce {
             input,
             group_key,
@@ -97,7 +107,9 @@ impl ReduceElision {
                 );
 
                 *relation = result;
+                return true;
             }
         }
+        false
     }
 }
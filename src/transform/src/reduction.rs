@@ -12,10 +12,10 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::iter;
 
-use expr::RelationExpr;
-use repr::{Datum, RowArena};
+use expr::{RelationExpr, ScalarExpr};
+use repr::{Datum, RelationType, RowArena};
 
-use crate::{TransformArgs, TransformError};
+use crate::{Transform, TransformArgs, TransformError};
 
 pub use demorgans::DeMorgans;
 pub use negate_predicate::NegatePredicate;
@@ -29,16 +29,32 @@ impl crate::Transform for FoldConstants {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), TransformError> {
-        relation.try_visit_mut(&mut |e| self.action(e))
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, TransformError> {
+        let mut changed = false;
+        relation.try_visit_mut(&mut |e| {
+            changed |= self.action(e)?;
+            Ok(())
+        })?;
+        Ok(changed)
     }
 }
 
 impl FoldConstants {
-    /// Replace operators on constants collections with constant collections.
-    pub fn action(&self, relation: &mut RelationExpr) -> Result<(), TransformError> {
+    /// Replace operators on constants collections with constant collections,
+    /// reporting whether it changed `relation`.
+    pub fn action(&self, relation: &mut RelationExpr) -> Result<bool, TransformError> {
         let relation_type = relation.typ();
+        let was_constant = matches!(relation, RelationExpr::Constant { .. });
+        let mut changed = false;
         match relation {
             RelationExpr::Constant { .. } => { /* handled after match */ }
             RelationExpr::Get { .. } => {}
@@ -51,7 +67,7 @@ impl FoldConstants {
                 expected_group_size: _,
             } => {
                 for aggregate in aggregates.iter_mut() {
-                    aggregate.expr.reduce(&input.typ());
+                    changed |= reduce_scalar(&mut aggregate.expr, &input.typ());
                 }
                 if let RelationExpr::Constant { rows, .. } = &**input {
                     // Build a map from `group_key` to `Vec<Vec<an, ..., a1>>)`,
@@ -160,7 +176,7 @@ impl FoldConstants {
                             current_type = current_type.with_key(key.clone());
                         }
                     }
-                    scalar.reduce(&current_type);
+                    changed |= reduce_scalar(scalar, &current_type);
                 }
 
                 if let RelationExpr::Constant { rows, .. } = &**input {
@@ -190,7 +206,7 @@ impl FoldConstants {
                 demand: _,
             } => {
                 for expr in exprs.iter_mut() {
-                    expr.reduce(&input.typ());
+                    changed |= reduce_scalar(expr, &input.typ());
                 }
 
                 if let RelationExpr::Constant { rows, .. } = &**input {
@@ -220,9 +236,11 @@ impl FoldConstants {
             }
             RelationExpr::Filter { input, predicates } => {
                 for predicate in predicates.iter_mut() {
-                    predicate.reduce(&input.typ());
+                    changed |= reduce_scalar(predicate, &input.typ());
                 }
+                let len = predicates.len();
                 predicates.retain(|p| !p.is_literal_true());
+                changed |= predicates.len() != len;
 
                 // If any predicate is false, reduce to the empty collection.
                 if predicates
@@ -320,6 +338,20 @@ impl FoldConstants {
                 // TODO: General constant folding for all constant inputs.
             }
             RelationExpr::Union { base, inputs } => {
+                // The constant inputs are merged into one, which goes last.
+                let constants = iter::once(&**base)
+                    .chain(&*inputs)
+                    .filter(|input| matches!(input, RelationExpr::Constant { .. }))
+                    .count();
+                let in_place = match inputs.last() {
+                    Some(RelationExpr::Constant { rows, typ }) => {
+                        constants == 1 && !rows.is_empty() && *typ == relation_type
+                    }
+                    Some(_) => constants == 0,
+                    None => false,
+                };
+                changed |= !in_place;
+
                 let mut rows = vec![];
                 let mut new_inputs = vec![];
 
@@ -350,6 +382,17 @@ impl FoldConstants {
         // nodes here, since the match arm above might install new constant
         // nodes.
         if let RelationExpr::Constant { rows, typ } = relation {
+            // Any other operator replaced by a constant is a change, as is a
+            // constant not already in canonical representation.
+            changed |= !was_constant
+                || rows.windows(2).any(|pair| pair[0].0 >= pair[1].0)
+                || rows.iter().any(|(_, diff)| *diff == 0);
+            let nullable = typ
+                .column_types
+                .iter()
+                .map(|col_type| col_type.nullable)
+                .collect::<Vec<_>>();
+
             // Reduce down to canonical representation.
             let mut accum = HashMap::new();
             for (row, cnt) in rows.iter() {
@@ -372,18 +415,30 @@ impl FoldConstants {
                     }
                 }
             }
+            changed |= typ
+                .column_types
+                .iter()
+                .map(|col_type| col_type.nullable)
+                .ne(nullable);
         }
 
-        Ok(())
+        Ok(changed)
     }
 }
 
+/// Reduces `expr`, reporting whether that changed it.
+fn reduce_scalar(expr: &mut ScalarExpr, relation_type: &RelationType) -> bool {
+    let before = expr.clone();
+    expr.reduce(relation_type);
+    *expr != before
+}
+
 /// Transforms !(a && b) into !a || !b and !(a || b) into !a && !b
 pub mod demorgans {
 
     use expr::{BinaryFunc, RelationExpr, ScalarExpr, UnaryFunc};
 
-    use crate::{TransformArgs, TransformError};
+    use crate::{Transform, TransformArgs, TransformError};
 
     /// Transforms !(a && b) into !a || !b and !(a || b) into !a && !b
     #[derive(Debug)]
@@ -392,32 +447,45 @@ pub mod demorgans {
         fn transform(
             &self,
             relation: &mut RelationExpr,
-            _: TransformArgs,
+            args: TransformArgs,
         ) -> Result<(), TransformError> {
+            self.transform_changed(relation, args).map(|_| ())
+        }
+
+        fn transform_changed(
+            &self,
+            relation: &mut RelationExpr,
+            _: TransformArgs,
+        ) -> Result<bool, TransformError> {
+            let mut changed = false;
             relation.visit_mut_pre(&mut |e| {
-                self.action(e);
+                changed |= self.action(e);
             });
-            Ok(())
+            Ok(changed)
         }
     }
 
     impl DeMorgans {
-        /// Transforms !(a && b) into !a || !b and !(a || b) into !a && !b
-        pub fn action(&self, relation: &mut RelationExpr) {
+        /// Transforms !(a && b) into !a || !b and !(a || b) into !a && !b,
+        /// reporting whether it did.
+        pub fn action(&self, relation: &mut RelationExpr) -> bool {
+            let mut changed = false;
             if let RelationExpr::Filter {
                 input: _,
                 predicates,
             } = relation
             {
                 for predicate in predicates.iter_mut() {
-                    demorgans(predicate);
+                    changed |= demorgans(predicate);
                 }
             }
+            changed
         }
     }
 
-    /// Transforms !(a && b) into !a || !b and !(a || b) into !a && !b
-    pub fn demorgans(expr: &mut ScalarExpr) {
+    /// Transforms !(a && b) into !a || !b and !(a || b) into !a && !b,
+    /// reporting whether it did.
+    pub fn demorgans(expr: &mut ScalarExpr) -> bool {
         if let ScalarExpr::CallUnary {
             expr: inner,
             func: UnaryFunc::Not,
@@ -438,7 +506,8 @@ pub mod demorgans {
                             expr1: Box::new(inner0),
                             expr2: Box::new(inner1),
                             func: BinaryFunc::Or,
-                        }
+                        };
+                        return true;
                     }
                     BinaryFunc::Or => {
                         let inner0 = ScalarExpr::CallUnary {
@@ -453,12 +522,14 @@ pub mod demorgans {
                             expr1: Box::new(inner0),
                             expr2: Box::new(inner1),
                             func: BinaryFunc::And,
-                        }
+                        };
+                        return true;
                     }
                     _ => {}
                 }
             }
         }
+        false
     }
 }
 
@@ -468,7 +539,7 @@ pub mod undistribute_and {
     use expr::{BinaryFunc, RelationExpr, ScalarExpr};
     use repr::{Datum, ScalarType};
 
-    use crate::{TransformArgs, TransformError};
+    use crate::{Transform, TransformArgs, TransformError};
 
     /// Transforms predicates from (a && b) || (a && c) into a && (b || c).
     #[derive(Debug)]
@@ -478,27 +549,39 @@ pub mod undistribute_and {
         fn transform(
             &self,
             relation: &mut RelationExpr,
-            _: TransformArgs,
+            args: TransformArgs,
         ) -> Result<(), TransformError> {
+            self.transform_changed(relation, args).map(|_| ())
+        }
+
+        fn transform_changed(
+            &self,
+            relation: &mut RelationExpr,
+            _: TransformArgs,
+        ) -> Result<bool, TransformError> {
+            let mut changed = false;
             relation.visit_mut(&mut |e| {
-                self.action(e);
+                changed |= self.action(e);
             });
-            Ok(())
+            Ok(changed)
         }
     }
 
     impl UndistributeAnd {
-        /// Transforms predicates from (a && b) || (a && c) into a && (b || c).
-        pub fn action(&self, relation: &mut RelationExpr) {
+        /// Transforms predicates from (a && b) || (a && c) into a && (b || c),
+        /// reporting whether it did.
+        pub fn action(&self, relation: &mut RelationExpr) -> bool {
+            let mut changed = false;
             if let RelationExpr::Filter {
                 input: _,
                 predicates,
             } = relation
             {
                 for predicate in predicates.iter_mut() {
-                    undistribute_and(predicate);
+                    changed |= undistribute_and(predicate);
                 }
             }
+            changed
         }
     }
 
@@ -539,13 +622,17 @@ pub mod undistribute_and {
         }
     }
 
-    /// Transforms (a && b) || (a && c) into a && (b || c)
-    pub fn undistribute_and(expr: &mut ScalarExpr) {
-        expr.visit_mut(&mut |x| undistribute_and_helper(x));
+    /// Transforms (a && b) || (a && c) into a && (b || c), reporting whether
+    /// it did.
+    pub fn undistribute_and(expr: &mut ScalarExpr) -> bool {
+        let mut changed = false;
+        expr.visit_mut(&mut |x| changed |= undistribute_and_helper(x));
+        changed
     }
 
-    /// AND undistribution to apply at each `ScalarExpr`.
-    pub fn undistribute_and_helper(expr: &mut ScalarExpr) {
+    /// AND undistribution to apply at each `ScalarExpr`, reporting whether it
+    /// applied.
+    pub fn undistribute_and_helper(expr: &mut ScalarExpr) -> bool {
         if let ScalarExpr::CallBinary {
             expr1,
             expr2,
@@ -570,6 +657,7 @@ pub mod undistribute_and {
                 suppress_ands(expr2, &intersection[..]);
             }
 
+            let changed = !intersection.is_empty();
             for and_term in intersection.into_iter() {
                 *expr = ScalarExpr::CallBinary {
                     expr1: Box::new(expr.take()),
@@ -577,7 +665,9 @@ pub mod undistribute_and {
                     func: BinaryFunc::And,
                 };
             }
+            return changed;
         }
+        false
     }
 }
 
@@ -585,7 +675,7 @@ pub mod undistribute_and {
 pub mod negate_predicate {
     use expr::{BinaryFunc, RelationExpr, ScalarExpr, UnaryFunc};
 
-    use crate::{TransformArgs, TransformError};
+    use crate::{Transform, TransformArgs, TransformError};
 
     /// Transforms `NOT(a <op> b)` to `a negate(<op>) b`.
     #[derive(Debug)]
@@ -595,10 +685,17 @@ pub mod negate_predicate {
         fn transform(
             &self,
             relation: &mut RelationExpr,
-            _: TransformArgs,
+            args: TransformArgs,
         ) -> Result<(), TransformError> {
-            self.action(relation);
-            Ok(())
+            self.transform_changed(relation, args).map(|_| ())
+        }
+
+        fn transform_changed(
+            &self,
+            relation: &mut RelationExpr,
+            _: TransformArgs,
+        ) -> Result<bool, TransformError> {
+            Ok(self.action(relation))
         }
     }
 
@@ -616,14 +713,19 @@ pub mod negate_predicate {
     }
 
     impl NegatePredicate {
-        /// Transforms `NOT(a <op> b)` to `a negate(<op>) b` if it exists.
-        pub fn action(&self, relation: &mut RelationExpr) {
-            relation.visit_scalars_mut(&mut |x| x.visit_mut(&mut negate_predicate))
+        /// Transforms `NOT(a <op> b)` to `a negate(<op>) b` if it exists,
+        /// reporting whether it did.
+        pub fn action(&self, relation: &mut RelationExpr) -> bool {
+            let mut changed = false;
+            relation
+                .visit_scalars_mut(&mut |x| x.visit_mut(&mut |e| changed |= negate_predicate(e)));
+            changed
         }
     }
 
-    /// Transforms `NOT(a <op> b)` to `a negate(<op>) b` if it exists.
-    pub fn negate_predicate(expr: &mut ScalarExpr) {
+    /// Transforms `NOT(a <op> b)` to `a negate(<op>) b` if it exists,
+    /// reporting whether it did.
+    pub fn negate_predicate(expr: &mut ScalarExpr) -> bool {
         if let ScalarExpr::CallUnary {
             expr: not_input,
             func: UnaryFunc::Not,
@@ -636,15 +738,20 @@ pub mod negate_predicate {
                             expr1: Box::new(expr1.take()),
                             expr2: Box::new(expr2.take()),
                             func: negated_func,
-                        }
+                        };
+                        return true;
                     }
                 }
                 ScalarExpr::CallUnary {
                     expr: inner_expr,
                     func: UnaryFunc::Not,
-                } => *expr = inner_expr.take(),
+                } => {
+                    *expr = inner_expr.take();
+                    return true;
+                }
                 _ => {}
             }
         }
+        false
     }
 }
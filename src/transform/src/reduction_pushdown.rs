@@ -11,7 +11,7 @@
 //!
 //! At the moment, this only absorbs Map operators into Reduce operators.
 
-use crate::{RelationExpr, TransformArgs};
+use crate::{RelationExpr, Transform, TransformArgs};
 
 /// Pushes Reduce operators toward sources.
 #[derive(Debug)]
@@ -21,18 +21,28 @@ impl crate::Transform for ReductionPushdown {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl ReductionPushdown {
-    /// Pushes Reduce operators toward sources.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Pushes Reduce operators toward sources, reporting whether it pushed
+    /// any.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
         if let RelationExpr::Reduce {
             input,
             group_key,
@@ -80,8 +90,10 @@ impl ReductionPushdown {
                     });
                 }
 
-                **input = inner.take_dangerous()
+                **input = inner.take_dangerous();
+                return true;
             }
         }
+        false
     }
 }
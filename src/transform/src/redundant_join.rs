@@ -26,7 +26,7 @@ use std::collections::HashMap;
 
 use expr::{Id, JoinInputMapper};
 
-use crate::{RelationExpr, ScalarExpr, TransformArgs};
+use crate::{RelationExpr, ScalarExpr, Transform, TransformArgs};
 
 /// Remove redundant collections of distinct elements from joins.
 #[derive(Debug)]
@@ -36,10 +36,19 @@ impl crate::Transform for RedundantJoin {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
-        self.action(relation, &mut HashMap::new());
-        Ok(())
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
+        self.action(relation, &mut HashMap::new(), &mut changed);
+        Ok(changed)
     }
 }
 
@@ -57,17 +66,20 @@ impl RedundantJoin {
     /// variant to remove "redundant" joins, those that can be determined to
     /// neither restrict nor augment one of the input relations. Consult the
     /// `find_redundancy` method and its documentation for more detail.
+    ///
+    /// Sets `changed` if it removes any join input.
     pub fn action(
         &self,
         relation: &mut RelationExpr,
         lets: &mut HashMap<Id, Vec<ProvInfo>>,
+        changed: &mut bool,
     ) -> Vec<ProvInfo> {
         match relation {
             RelationExpr::Let { id, value, body } => {
                 // Recursively determine provenance of the value.
-                let value_prov = self.action(value, lets);
+                let value_prov = self.action(value, lets, changed);
                 let old = lets.insert(Id::Local(*id), value_prov);
-                let result = self.action(body, lets);
+                let result = self.action(body, lets, changed);
                 if let Some(old) = old {
                     lets.insert(Id::Local(*id), old);
                 } else {
@@ -102,7 +114,7 @@ impl RedundantJoin {
                 // Recursively apply transformation, and determine the provenance of inputs.
                 let input_prov = inputs
                     .iter_mut()
-                    .map(|i| self.action(i, lets))
+                    .map(|i| self.action(i, lets, changed))
                     .collect::<Vec<_>>();
 
                 // Determine useful information about the structure of the inputs.
@@ -130,6 +142,7 @@ impl RedundantJoin {
                 {
                     inputs.remove(input);
                     input_types.remove(input);
+                    *changed = true;
 
                     let new_input_mapper = JoinInputMapper::new_from_input_types(&input_types);
                     // From `binding`, we produce the projection we will apply to the join
@@ -199,21 +212,25 @@ impl RedundantJoin {
 
             RelationExpr::Filter { input, .. } => {
                 // Filter may drop records, and so we unset `exact`.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, changed);
                 for prov in result.iter_mut() {
                     prov.exact = false;
                 }
                 result
             }
 
-            RelationExpr::Map { input, .. } => self.action(input, lets),
+            RelationExpr::Map { input, .. } => self.action(input, lets, changed),
 
             RelationExpr::Union { base, inputs } => {
-                let mut prov = self.action(base, lets);
+                let mut prov = self.action(base, lets, changed);
                 for input in inputs {
                     let mut new_prov = Vec::new();
                     for l in prov {
-                        new_prov.extend(self.action(input, lets).iter().flat_map(|r| l.meet(r)))
+                        new_prov.extend(
+                            self.action(input, lets, changed)
+                                .iter()
+                                .flat_map(|r| l.meet(r)),
+                        )
                     }
                     prov = new_prov;
                 }
@@ -227,7 +244,7 @@ impl RedundantJoin {
             } => {
                 // Reduce yields its first few columns as a key, and produces
                 // all key tuples that were present in its input.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, changed);
                 for prov in result.iter_mut() {
                     // update the bindings. no need to update `exact`.
                     let new_bindings = group_key
@@ -260,7 +277,7 @@ impl RedundantJoin {
 
             RelationExpr::Threshold { input } => {
                 // Threshold may drop records, and so we unset `exact`.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, changed);
                 for prov in result.iter_mut() {
                     prov.exact = false;
                 }
@@ -269,7 +286,7 @@ impl RedundantJoin {
 
             RelationExpr::TopK { input, .. } => {
                 // TopK may drop records, and so we unset `exact`.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, changed);
                 for prov in result.iter_mut() {
                     prov.exact = false;
                 }
@@ -279,7 +296,7 @@ impl RedundantJoin {
             RelationExpr::Project { input, outputs } => {
                 // Projections re-order, drop, and duplicate columns,
                 // but they neither drop rows nor invent values.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, changed);
                 for provenance in result.iter_mut() {
                     let new_binding = outputs
                         .iter()
@@ -300,7 +317,7 @@ impl RedundantJoin {
 
             RelationExpr::FlatMap { input, .. } => {
                 // FlatMap may drop records, and so we unset `exact`.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, changed);
                 for prov in result.iter_mut() {
                     prov.exact = false;
                 }
@@ -313,14 +330,14 @@ impl RedundantJoin {
                 // been a problem in `Union`, where we might report
                 // that the union of positive and negative records is
                 // "exact": cancellations would make this false.
-                let mut result = self.action(input, lets);
+                let mut result = self.action(input, lets, changed);
                 for prov in result.iter_mut() {
                     prov.exact = false;
                 }
                 result
             }
 
-            RelationExpr::ArrangeBy { input, .. } => self.action(input, lets),
+            RelationExpr::ArrangeBy { input, .. } => self.action(input, lets, changed),
         }
     }
 }
@@ -9,7 +9,7 @@
 
 //! Transforms predicates of the form "A and B" into two: "A" and "B".
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 use expr::{BinaryFunc, RelationExpr, ScalarExpr};
 
 /// Transforms predicates of the form "A and B" into two: "A" and "B".
@@ -21,10 +21,22 @@ impl crate::Transform for SplitPredicates {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut(&mut |expr| {
             if let RelationExpr::Filter { predicates, .. } = expr {
+                // Predicates are popped from the back, which reverses their
+                // order, and so changes any filter of more than one.
+                changed |= predicates.len() > 1;
                 let mut pending_predicates = predicates.drain(..).collect::<Vec<_>>();
                 while let Some(expr) = pending_predicates.pop() {
                     if let ScalarExpr::CallBinary {
@@ -35,12 +47,13 @@ impl crate::Transform for SplitPredicates {
                     {
                         pending_predicates.push(*expr1);
                         pending_predicates.push(*expr2);
+                        changed = true;
                     } else {
                         predicates.push(expr);
                     }
                 }
             }
         });
-        Ok(())
+        Ok(changed)
     }
 }
@@ -0,0 +1,41 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Helpers for constructing relations in tests.
+
+use expr::RelationExpr;
+use repr::{Datum, RelationType, ScalarType};
+
+/// A constant collection of `rows` of type `typ`, in which `None` is null.
+///
+/// Panics if a row does not have the arity of `typ`, or if a datum is not of
+/// the type of its column, e.g. a null in a non-nullable column.
+pub fn constant_with_rows(typ: RelationType, rows: Vec<Vec<Option<Datum>>>) -> RelationExpr {
+    let rows = rows
+        .into_iter()
+        .map(|row| {
+            assert_eq!(row.len(), typ.arity(), "row {:?} of the wrong arity", row);
+            row.into_iter()
+                .map(|datum| datum.unwrap_or(Datum::Null))
+                .collect()
+        })
+        .collect();
+    RelationExpr::constant(rows, typ)
+}
+
+/// A constant collection of `arity` nullable `Int64` columns, in which `None`
+/// is null.
+pub fn int64_constant(arity: usize, rows: Vec<Vec<Option<i64>>>) -> RelationExpr {
+    let typ = RelationType::new(vec![ScalarType::Int64.nullable(true); arity]);
+    let rows = rows
+        .into_iter()
+        .map(|row| row.into_iter().map(|i| i.map(Datum::Int64)).collect())
+        .collect();
+    constant_with_rows(typ, rows)
+}
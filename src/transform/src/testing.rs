@@ -0,0 +1,50 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Checks of the changes transforms make, for use in tests.
+//!
+//! These are available outside of the crate, so that tests which run
+//! transforms over plans written elsewhere, e.g. as datadriven cases, can
+//! check the same invariants as the unit tests.
+
+use std::collections::HashMap;
+
+use expr::RelationExpr;
+use repr::Row;
+
+/// Asserts that the rows of the `Constant`s in `after` are among those in
+/// `before`, each with its multiplicity, with no more copies of any.
+///
+/// A transform that only discards rows or replaces expressions with empty
+/// collections, as `NonNullRequirements` does, must satisfy this, as none of
+/// the rows it keeps may change.
+pub fn assert_constant_rows_retained(before: &RelationExpr, after: &RelationExpr) {
+    fn constant_rows(relation: &RelationExpr) -> HashMap<(Row, isize), usize> {
+        let mut rows = HashMap::new();
+        relation.visit(&mut |e| {
+            if let RelationExpr::Constant { rows: constant, .. } = e {
+                for row in constant {
+                    *rows.entry(row.clone()).or_insert(0) += 1;
+                }
+            }
+        });
+        rows
+    }
+    let before = constant_rows(before);
+    for (row, count) in constant_rows(after) {
+        let existing = before.get(&row).copied().unwrap_or(0);
+        assert!(
+            count <= existing,
+            "row {:?} appears {} times after the transform, but {} before",
+            row,
+            count,
+            existing
+        );
+    }
+}
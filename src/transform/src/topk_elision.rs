@@ -9,7 +9,7 @@
 
 //! Remove TopK operators with both an offset of zero and no limit.
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 use expr::RelationExpr;
 
 /// Remove TopK operators with both an offset of zero and no limit.
@@ -20,18 +20,28 @@ impl crate::Transform for TopKElision {
     fn transform(
         &self,
         relation: &mut RelationExpr,
-        _: TransformArgs,
+        args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        _: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
+        let mut changed = false;
         relation.visit_mut(&mut |e| {
-            self.action(e);
+            changed |= self.action(e);
         });
-        Ok(())
+        Ok(changed)
     }
 }
 
 impl TopKElision {
-    /// Remove TopK operators with both an offset of zero and no limit.
-    pub fn action(&self, relation: &mut RelationExpr) {
+    /// Remove TopK operators with both an offset of zero and no limit,
+    /// reporting whether `relation` was one.
+    pub fn action(&self, relation: &mut RelationExpr) -> bool {
         if let RelationExpr::TopK {
             input,
             group_key: _,
@@ -43,7 +53,9 @@ impl TopKElision {
         {
             if limit.is_none() && *offset == 0 {
                 *relation = input.take_dangerous();
+                return true;
             }
         }
+        false
     }
 }
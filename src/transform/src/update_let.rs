@@ -12,7 +12,7 @@
 
 use std::collections::HashMap;
 
-use crate::TransformArgs;
+use crate::{Transform, TransformArgs};
 use expr::{Id, IdGen, LocalId, RelationExpr};
 use repr::RelationType;
 
@@ -31,44 +31,56 @@ impl crate::Transform for UpdateLet {
         relation: &mut RelationExpr,
         args: TransformArgs,
     ) -> Result<(), crate::TransformError> {
+        self.transform_changed(relation, args).map(|_| ())
+    }
+
+    fn transform_changed(
+        &self,
+        relation: &mut RelationExpr,
+        args: TransformArgs,
+    ) -> Result<bool, crate::TransformError> {
         *args.id_gen = IdGen::default(); // Get a fresh IdGen.
-        self.action(relation, &mut HashMap::new(), args.id_gen);
-        Ok(())
+        Ok(self.action(relation, &mut HashMap::new(), args.id_gen))
     }
 }
 
 impl UpdateLet {
-    /// Re-assign type information and identifier to each `Get`.
+    /// Re-assign type information and identifier to each `Get`, reporting
+    /// whether any changed.
     pub fn action(
         &self,
         relation: &mut RelationExpr,
         remap: &mut HashMap<LocalId, (LocalId, RelationType)>,
         id_gen: &mut IdGen,
-    ) {
+    ) -> bool {
+        let mut changed = false;
         match relation {
             RelationExpr::Let { id, value, body } => {
-                self.action(value, remap, id_gen);
+                changed |= self.action(value, remap, id_gen);
                 // If a local id, assign a new identifier and refresh the type.
                 let new_id = LocalId::new(id_gen.allocate_id());
                 let prev = remap.insert(id.clone(), (new_id, value.typ()));
-                self.action(body, remap, id_gen);
+                changed |= self.action(body, remap, id_gen);
                 remap.remove(id);
                 if let Some(prev_stuff) = prev {
                     remap.insert(id.clone(), prev_stuff);
                 }
+                changed |= *id != new_id;
                 *id = new_id;
             }
             RelationExpr::Get { id, typ } => {
                 if let Id::Local(local_id) = id {
                     if let Some((new_id, new_type)) = remap.get(local_id) {
+                        changed |= local_id != new_id || typ != new_type;
                         *local_id = new_id.clone();
                         *typ = new_type.clone()
                     }
                 }
             }
             _ => {
-                relation.visit1_mut(&mut |e| self.action(e, remap, id_gen));
+                relation.visit1_mut(&mut |e| changed |= self.action(e, remap, id_gen));
             }
         }
+        changed
     }
 }
@@ -0,0 +1,68 @@
+// Copyright Materialize, Inc. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Utilities shared by transforms.
+
+use std::collections::{HashMap, HashSet};
+
+/// The columns contained in each of `sets`, or none if `sets` is empty.
+pub(crate) fn intersect_column_sets(mut sets: Vec<HashSet<usize>>) -> HashSet<usize> {
+    match sets.len() {
+        0 => HashSet::new(),
+        1 => sets.pop().unwrap(),
+        len => {
+            // A column is in the intersection exactly when each of the sets
+            // contains it.
+            let mut counts = HashMap::new();
+            for set in sets.iter() {
+                for col in set.iter() {
+                    *counts.entry(*col).or_insert(0) += 1;
+                }
+            }
+            counts
+                .into_iter()
+                .filter(|(_, count)| *count == len)
+                .map(|(col, _)| col)
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::intersect_column_sets;
+
+    fn set(columns: &[usize]) -> HashSet<usize> {
+        columns.iter().cloned().collect()
+    }
+
+    #[test]
+    fn intersect_empty() {
+        assert_eq!(intersect_column_sets(vec![]), set(&[]));
+        assert_eq!(intersect_column_sets(vec![set(&[])]), set(&[]));
+    }
+
+    #[test]
+    fn intersect_single() {
+        assert_eq!(intersect_column_sets(vec![set(&[0, 2])]), set(&[0, 2]));
+    }
+
+    #[test]
+    fn intersect_multiple() {
+        let overlapping = vec![set(&[0, 1, 2]), set(&[1, 2, 3]), set(&[2, 1])];
+        assert_eq!(intersect_column_sets(overlapping), set(&[1, 2]));
+        let disjoint = vec![set(&[0, 1]), set(&[2, 3])];
+        assert_eq!(intersect_column_sets(disjoint), set(&[]));
+        // An empty set among others empties the intersection.
+        let with_empty = vec![set(&[0, 1]), set(&[]), set(&[0, 1])];
+        assert_eq!(intersect_column_sets(with_empty), set(&[]));
+    }
+}
@@ -17,7 +17,6 @@ use anyhow::{anyhow, Error};
 /// TODO(justin):
 /// * It's currently missing a mechanism to run just a single test file
 /// * There is some duplication between this and the SQL planner
-/// * Not all operators supported (Reduce)
 
 #[derive(Debug, Clone)]
 enum Sexp {
@@ -99,6 +98,23 @@ impl SexpParser {
 
                 Ok(Sexp::List(result))
             }
+            // String literals are atoms that keep their quotes, which tell them
+            // apart from names and column references.
+            Some('"') => {
+                let start = self.i;
+                self.i += 1;
+                while self.peek() != Some('"') {
+                    if self.peek().is_none() {
+                        return Err(anyhow!("unterminated string"));
+                    }
+                    self.i += 1;
+                }
+                self.i += 1;
+                let end = self.i;
+                self.munch();
+                let word: String = self.s[start..end].iter().collect();
+                Ok(Sexp::Atom(word))
+            }
             Some(ch) if SexpParser::is_atom_char(ch) => {
                 let start = self.i;
                 while let Some(ch) = self.peek() {
@@ -130,10 +146,15 @@ impl SexpParser {
 mod tests {
     use super::{Sexp, SexpParser};
     use anyhow::{anyhow, bail, Error};
-    use expr::{GlobalId, Id, IdHumanizer, JoinImplementation, LocalId, RelationExpr, ScalarExpr};
+    use expr::{
+        AggregateExpr, AggregateFunc, BinaryFunc, ColumnOrder, GlobalId, Id, IdHumanizer,
+        JoinImplementation, LocalId, RelationExpr, ScalarExpr, TableFunc, UnaryFunc, VariadicFunc,
+    };
     use repr::{ColumnType, Datum, RelationType, Row, ScalarType};
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
     use std::fmt::Write;
+    use transform::nonnull_requirements::NonNullRequirements;
+    use transform::testing::assert_constant_rows_retained;
     use transform::{Optimizer, Transform, TransformArgs};
 
     #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -141,6 +162,7 @@ mod tests {
         Build,
         Opt,
         Steps,
+        Requirements,
     }
 
     #[derive(Debug)]
@@ -227,13 +249,6 @@ mod tests {
         }
     }
 
-    fn try_list_of_atoms(s: &Sexp) -> Result<Vec<String>, Error> {
-        match s {
-            Sexp::List(s) => s.iter().map(|s| try_atom(s)).collect(),
-            _ => Err(anyhow!("expected {} to be a list", s)),
-        }
-    }
-
     /// Extracts a column reference expression from a Sexp.
     fn extract_idx(s: Sexp) -> Result<usize, Error> {
         match s {
@@ -288,6 +303,13 @@ mod tests {
                 input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
                 scalars: build_scalar_list(nth(&s, 2)?)?,
             }),
+            // (flat-map <input> <table func> [expressions])
+            "flat-map" => Ok(RelationExpr::FlatMap {
+                input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
+                func: build_table_func(&try_atom(&nth(&s, 2)?)?)?,
+                exprs: build_scalar_list(nth(&s, 3)?)?,
+                demand: None,
+            }),
             // (project <input> [<col refs>])
             "project" => Ok(RelationExpr::Project {
                 input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
@@ -370,10 +392,51 @@ mod tests {
                     inputs: inputs[1..].to_vec(),
                 })
             }
+            // (reduce <input> [<group key expressions>] [(<aggregate func> <expression>)...])
+            "reduce" => Ok(RelationExpr::Reduce {
+                input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
+                group_key: build_scalar_list(nth(&s, 2)?)?,
+                aggregates: try_list(nth(&s, 3)?)?
+                    .into_iter()
+                    .map(build_aggregate)
+                    .collect::<Result<Vec<AggregateExpr>, Error>>()?,
+                monotonic: false,
+                expected_group_size: None,
+            }),
+            // (top-k <input> [<group key col refs>] [<order key col refs>] <limit> <offset>)
+            //
+            // The order key is ascending in each column, and a limit of `null`
+            // is no limit.
+            "top-k" => Ok(RelationExpr::TopK {
+                input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
+                group_key: try_list(nth(&s, 2)?)?
+                    .into_iter()
+                    .map(extract_idx)
+                    .collect::<Result<Vec<usize>, Error>>()?,
+                order_key: try_list(nth(&s, 3)?)?
+                    .into_iter()
+                    .map(|s| {
+                        Ok(ColumnOrder {
+                            column: extract_idx(s)?,
+                            desc: false,
+                        })
+                    })
+                    .collect::<Result<Vec<ColumnOrder>, Error>>()?,
+                limit: match try_atom(&nth(&s, 4)?)?.as_str() {
+                    "null" => None,
+                    limit => Some(limit.parse()?),
+                },
+                offset: try_atom(&nth(&s, 5)?)?.parse()?,
+                monotonic: false,
+            }),
             // (negate <input>)
             "negate" => Ok(RelationExpr::Negate {
                 input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
             }),
+            // (threshold <input>)
+            "threshold" => Ok(RelationExpr::Threshold {
+                input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
+            }),
             // (filter <input> <predicate>)
             "filter" => Ok(RelationExpr::Filter {
                 input: Box::new(build_rel(nth(&s, 1)?, catalog, scope)?),
@@ -387,7 +450,6 @@ mod tests {
                     .map(build_scalar_list)
                     .collect::<Result<Vec<Vec<ScalarExpr>>, Error>>()?,
             }),
-            // TODO(justin): add the rest of the operators.
             name => Err(anyhow!("expected {} to be a relational operator", name)),
         }
     }
@@ -404,6 +466,8 @@ mod tests {
         match s {
             // TODO(justin): support more scalar exprs.
             Sexp::Atom(s) => match s.as_str() {
+                // A null of type int64, the type of the columns it usually pads.
+                "null" => Ok(ScalarExpr::literal_null(ScalarType::Int64.nullable(true))),
                 "true" => Ok(ScalarExpr::literal(
                     Ok(Datum::True),
                     ColumnType {
@@ -427,6 +491,10 @@ mod tests {
                         Some('#') => {
                             Ok(ScalarExpr::Column(extract_idx(Sexp::Atom(s.to_string()))?))
                         }
+                        Some('"') => Ok(ScalarExpr::literal_ok(
+                            Datum::String(&s[1..s.len() - 1]),
+                            ScalarType::String.nullable(false),
+                        )),
                         Some('0') | Some('1') | Some('2') | Some('3') | Some('4') | Some('5')
                         | Some('6') | Some('7') | Some('8') | Some('9') => Ok(ScalarExpr::literal(
                             Ok(Datum::Int64(s.parse::<i64>()?)),
@@ -436,26 +504,124 @@ mod tests {
                     }
                 }
             },
-            s => Err(anyhow!("expected {} to be a scalar", s)),
+            s => match try_atom(&nth(&s, 0)?)?.as_str() {
+                // (call_unary <func> <expression>)
+                "call_unary" => Ok(build_scalar(nth(&s, 2)?)?
+                    .call_unary(build_unary_func(&try_atom(&nth(&s, 1)?)?)?)),
+                // (call_binary <func> <expression> <expression>)
+                "call_binary" => Ok(build_scalar(nth(&s, 2)?)?.call_binary(
+                    build_scalar(nth(&s, 3)?)?,
+                    build_binary_func(&try_atom(&nth(&s, 1)?)?)?,
+                )),
+                // (call_variadic <func> [expressions])
+                "call_variadic" => Ok(ScalarExpr::CallVariadic {
+                    func: build_variadic_func(&try_atom(&nth(&s, 1)?)?)?,
+                    exprs: build_scalar_list(nth(&s, 2)?)?,
+                }),
+                // (if <cond> <then> <else>)
+                "if" => Ok(build_scalar(nth(&s, 1)?)?
+                    .if_then_else(build_scalar(nth(&s, 2)?)?, build_scalar(nth(&s, 3)?)?)),
+                _ => Err(anyhow!("expected {} to be a scalar", s)),
+            },
         }
     }
 
-    fn parse_type_list(s: Sexp) -> Result<RelationType, Error> {
-        let types = try_list_of_atoms(&s)?;
+    // TODO(justin): the functions below are only those that tests have
+    // needed so far, and are named after their variants in snake case.
+    fn build_unary_func(name: &str) -> Result<UnaryFunc, Error> {
+        match name {
+            "not" => Ok(UnaryFunc::Not),
+            "is_null" => Ok(UnaryFunc::IsNull),
+            "neg_int64" => Ok(UnaryFunc::NegInt64),
+            "cast_int64_to_int32" => Ok(UnaryFunc::CastInt64ToInt32),
+            "cast_int64_to_float64" => Ok(UnaryFunc::CastInt64ToFloat64),
+            "cast_int64_to_string" => Ok(UnaryFunc::CastInt64ToString),
+            "cast_string_to_int32" => Ok(UnaryFunc::CastStringToInt32),
+            "byte_length_string" => Ok(UnaryFunc::ByteLengthString),
+            "char_length" => Ok(UnaryFunc::CharLength),
+            "trim_whitespace" => Ok(UnaryFunc::TrimWhitespace),
+            _ => Err(anyhow!("no unary function named {}", name)),
+        }
+    }
+
+    fn build_binary_func(name: &str) -> Result<BinaryFunc, Error> {
+        match name {
+            "and" => Ok(BinaryFunc::And),
+            "or" => Ok(BinaryFunc::Or),
+            "eq" => Ok(BinaryFunc::Eq),
+            "not_eq" => Ok(BinaryFunc::NotEq),
+            "lt" => Ok(BinaryFunc::Lt),
+            "lte" => Ok(BinaryFunc::Lte),
+            "gt" => Ok(BinaryFunc::Gt),
+            "gte" => Ok(BinaryFunc::Gte),
+            "add_int64" => Ok(BinaryFunc::AddInt64),
+            "trim" => Ok(BinaryFunc::Trim),
+            "date_part_timestamp" => Ok(BinaryFunc::DatePartTimestamp),
+            "date_trunc_timestamp" => Ok(BinaryFunc::DateTruncTimestamp),
+            "jsonb_get_string_stringify" => Ok(BinaryFunc::JsonbGetString { stringify: true }),
+            _ => Err(anyhow!("no binary function named {}", name)),
+        }
+    }
+
+    fn build_variadic_func(name: &str) -> Result<VariadicFunc, Error> {
+        match name {
+            "coalesce" => Ok(VariadicFunc::Coalesce),
+            "substr" => Ok(VariadicFunc::Substr),
+            "regexp_match" => Ok(VariadicFunc::RegexpMatch),
+            _ => Err(anyhow!("no variadic function named {}", name)),
+        }
+    }
+
+    fn build_table_func(name: &str) -> Result<TableFunc, Error> {
+        match name {
+            "generate_series_int64" => Ok(TableFunc::GenerateSeriesInt64),
+            _ => Err(anyhow!("no table function named {}", name)),
+        }
+    }
 
-        let col_types = types
+    /// Builds an aggregate from `(<aggregate func> <expression>)`.
+    fn build_aggregate(s: Sexp) -> Result<AggregateExpr, Error> {
+        let func = match try_atom(&nth(&s, 0)?)?.as_str() {
+            "count" => AggregateFunc::Count,
+            "sum_int64" => AggregateFunc::SumInt64,
+            "min_int64" => AggregateFunc::MinInt64,
+            name => bail!("no aggregate function named {}", name),
+        };
+        Ok(AggregateExpr {
+            func,
+            expr: build_scalar(nth(&s, 1)?)?,
+            distinct: false,
+        })
+    }
+
+    fn parse_type_list(s: Sexp) -> Result<RelationType, Error> {
+        let col_types = try_list(s)?
             .iter()
-            .map(|e| match e.as_str() {
-                "int32" => Ok(ScalarType::Int32.nullable(true)),
-                "int64" => Ok(ScalarType::Int64.nullable(true)),
-                "bool" => Ok(ScalarType::Bool.nullable(true)),
-                _ => Err(anyhow!("unknown type {}", e)),
-            })
+            .map(parse_type)
             .collect::<Result<Vec<ColumnType>, Error>>()?;
 
         Ok(RelationType::new(col_types))
     }
 
+    /// Parses a nullable column type, or a non-nullable one as `(non-null <type>)`.
+    fn parse_type(s: &Sexp) -> Result<ColumnType, Error> {
+        if let Sexp::List(_) = s {
+            return match try_atom(&nth(s, 0)?)?.as_str() {
+                "non-null" => Ok(parse_type(&nth(s, 1)?)?.nullable(false)),
+                _ => Err(anyhow!("unknown type {}", s)),
+            };
+        }
+        match try_atom(s)?.as_str() {
+            "int32" => Ok(ScalarType::Int32.nullable(true)),
+            "int64" => Ok(ScalarType::Int64.nullable(true)),
+            "bool" => Ok(ScalarType::Bool.nullable(true)),
+            "string" => Ok(ScalarType::String.nullable(true)),
+            "timestamp" => Ok(ScalarType::Timestamp.nullable(true)),
+            "jsonb" => Ok(ScalarType::Jsonb.nullable(true)),
+            _ => Err(anyhow!("unknown type {}", s)),
+        }
+    }
+
     fn handle_cat(s: Sexp, cat: &mut TestCatalog) -> Result<(), Error> {
         match try_atom(&nth(&s, 0)?)?.as_str() {
             "defsource" => {
@@ -485,19 +651,15 @@ mod tests {
         let mut id_gen = Default::default();
         let indexes = HashMap::new();
         for t in args.get("apply").cloned().unwrap_or_else(Vec::new).iter() {
-            get_transform(t)?.transform(
-                &mut rel,
-                TransformArgs {
-                    id_gen: &mut id_gen,
-                    indexes: &indexes,
-                },
-            )?;
+            get_transform(t)?.transform(&mut rel, TransformArgs::new(&mut id_gen, &indexes))?;
+            check_requirements_retain_rows(&rel);
         }
 
         match test_type {
             TestType::Opt => {
                 let mut opt: Optimizer = Default::default();
                 rel = opt.optimize(rel, &HashMap::new()).unwrap().into_inner();
+                check_requirements_retain_rows(&rel);
 
                 Ok(rel.explain(cat).to_string())
             }
@@ -515,13 +677,8 @@ mod tests {
 
                 for transform in opt.transforms.iter() {
                     let prev = rel.clone();
-                    transform.transform(
-                        &mut rel,
-                        TransformArgs {
-                            id_gen: &mut id_gen,
-                            indexes: &indexes,
-                        },
-                    )?;
+                    transform.transform(&mut rel, TransformArgs::new(&mut id_gen, &indexes))?;
+                    check_requirements_retain_rows(&rel);
 
                     if rel != prev {
                         if no_change.len() > 0 {
@@ -559,9 +716,70 @@ mod tests {
 
                 Ok(out)
             }
+            TestType::Requirements => {
+                check_requirements_retain_rows(&rel);
+                let analysis = NonNullRequirements::default().analyze(&rel)?;
+                let mut out = format_requirements(&analysis.gets, cat);
+                // The branches the analysis proves empty follow, by their paths.
+                for path in analysis.eliminable_paths {
+                    writeln!(out, "eliminable: {:?}", path)?;
+                }
+                Ok(out)
+            }
         }
     }
 
+    /// Applies `NonNullRequirements` to a copy of `rel`, and asserts that the
+    /// constants of the result keep only rows of the constants of `rel`.
+    ///
+    /// A plan the transform rejects is left unchecked, and its error is
+    /// reported by the directive that produced the plan, if at all.
+    fn check_requirements_retain_rows(rel: &RelationExpr) {
+        let mut pruned = rel.clone();
+        let result = NonNullRequirements::default().transform(
+            &mut pruned,
+            TransformArgs::new(&mut Default::default(), &HashMap::new()),
+        );
+        if result.is_ok() {
+            assert_constant_rows_retained(rel, &pruned);
+        }
+    }
+
+    /// Formats the requirements at each `Get`, one line per reference, in an
+    /// order that does not depend on the iteration order of maps and sets.
+    ///
+    /// Identifiers are sorted by name, and the references to each are listed
+    /// in the order the analysis visited them.
+    fn format_requirements(
+        requirements: &HashMap<Id, Vec<HashSet<usize>>>,
+        cat: &TestCatalog,
+    ) -> String {
+        let mut ids = requirements
+            .iter()
+            .map(|(id, needs)| {
+                let name = match id {
+                    Id::Global(id) => cat.humanize_id(*id).unwrap_or_else(|| id.to_string()),
+                    Id::Local(id) => id.to_string(),
+                };
+                (name, needs)
+            })
+            .collect::<Vec<_>>();
+        ids.sort_by(|(name1, _), (name2, _)| name1.cmp(name2));
+        let mut out = String::new();
+        for (name, needs) in ids {
+            for need in needs {
+                let mut columns = need.iter().collect::<Vec<_>>();
+                columns.sort();
+                let columns = columns
+                    .into_iter()
+                    .map(|c| format!("#{}", c))
+                    .collect::<Vec<_>>();
+                out.push_str(&format!("{}: ({})\n", name, columns.join(", ")));
+            }
+        }
+        out
+    }
+
     fn get_transform(name: &str) -> Result<Box<dyn Transform>, Error> {
         // TODO(justin): is there a way to just extract these from the Optimizer list of
         // transforms?
@@ -604,6 +822,12 @@ mod tests {
                         Ok(msg) => msg,
                         Err(err) => format!("error: {}\n", err),
                     },
+                    "requirements" => {
+                        match run_testcase(&s.input, &catalog, &s.args, TestType::Requirements) {
+                            Ok(msg) => msg,
+                            Err(err) => format!("error: {}\n", err),
+                        }
+                    }
                     _ => panic!("unknown directive: {}", s.directive),
                 }
             })